@@ -2,11 +2,19 @@
 
 use std::{
     error::Error,
-    io,
+    io, thread,
     time::{Duration, Instant},
 };
 
-use crate::{cpu::CpuMonitor, gpu::GpuMonitor, ui};
+use crate::{
+    cgroup::tree,
+    cli::Options,
+    monitors::Monitors,
+    process::{self, control::SchedulingEdit, detail::ProcessDetail, GroupBy, SortKey},
+    prometheus::Exporter,
+    push::Pusher,
+    ui::{self, Screen, ViewState},
+};
 use crossterm::{
     event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind},
     execute,
@@ -17,17 +25,69 @@ use ratatui::{
     Terminal,
 };
 
+/// How often `rustop serve` re-samples the monitors.
+const SERVE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Metrics outputs fed from the refresh loop.
+struct Outputs {
+    exporter: Option<Exporter>,
+    pusher: Option<Pusher>,
+}
+
+impl Outputs {
+    fn open(options: &Options) -> io::Result<Self> {
+        Ok(Self {
+            exporter: options.prometheus.map(Exporter::start).transpose()?,
+            pusher: Pusher::new(options)?,
+        })
+    }
+
+    /// Hand fresh readings to every output; returns the outcome of any push
+    /// that finished since the last call.
+    fn update(&mut self, monitors: &Monitors) -> Option<io::Result<()>> {
+        if let Some(exporter) = &self.exporter {
+            exporter.update(monitors);
+        }
+        self.pusher.as_mut()?.update(monitors)
+    }
+}
+
+/// Run the metrics outputs without the TUI until interrupted.
+pub fn serve(options: Options) -> Result<(), Box<dyn Error>> {
+    let mut monitors = Monitors::new();
+    let mut outputs = Outputs::open(&options)?;
+    if let Some(exporter) = &outputs.exporter {
+        eprintln!(
+            "rustop: serving metrics on http://{}/metrics",
+            exporter.addr()
+        );
+    }
+    loop {
+        // Publish before sleeping so the first scrape is not empty.
+        // A collector that is down should not stop the others; keep retrying.
+        if let Some(Err(err)) = outputs.update(&monitors) {
+            eprintln!("rustop: push failed: {err}");
+        }
+        thread::sleep(SERVE_INTERVAL);
+        monitors.refresh();
+    }
+}
+
 /// Entry point invoked from `main` to run the TUI.
-pub fn run() -> Result<(), Box<dyn Error>> {
+pub fn run(options: Options) -> Result<(), Box<dyn Error>> {
+    // Open outputs before touching the terminal so a busy port is reported plainly.
+    let mut outputs = Outputs::open(&options)?;
     enable_raw_mode()?;
     let mut stdout = io::stdout();
     execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    let mut cpu_monitor = CpuMonitor::new();
-    let mut gpu_monitor = GpuMonitor::new();
-    let res = run_app(&mut terminal, &mut cpu_monitor, &mut gpu_monitor);
+    let mut monitors = Monitors::new();
+    if let Some(secs) = options.d_state_secs {
+        monitors.process.set_d_state_secs(secs);
+    }
+    let res = run_app(&mut terminal, &mut monitors, options, &mut outputs);
 
     disable_raw_mode()?;
     execute!(
@@ -42,14 +102,20 @@ pub fn run() -> Result<(), Box<dyn Error>> {
 
 fn run_app<B: Backend>(
     terminal: &mut Terminal<B>,
-    cpu_monitor: &mut CpuMonitor,
-    gpu_monitor: &mut GpuMonitor,
+    monitors: &mut Monitors,
+    options: Options,
+    outputs: &mut Outputs,
 ) -> io::Result<()> {
     let tick_rate = Duration::from_millis(500);
     let mut last_tick = Instant::now();
+    let mut view = ViewState {
+        truecolor: ui::supports_truecolor(),
+        units: options.units,
+        ..ViewState::default()
+    };
 
     loop {
-        terminal.draw(|frame| ui::draw(frame, cpu_monitor, gpu_monitor))?;
+        terminal.draw(|frame| ui::draw(frame, monitors, &view))?;
 
         let timeout = tick_rate
             .checked_sub(last_tick.elapsed())
@@ -57,19 +123,200 @@ fn run_app<B: Backend>(
 
         if event::poll(timeout)? {
             if let Event::Key(key_event) = event::read()? {
-                if key_event.kind == KeyEventKind::Press {
-                    match key_event.code {
-                        KeyCode::F(10) | KeyCode::Char('q') | KeyCode::Char('Q') => return Ok(()),
-                        _ => {}
-                    }
+                if key_event.kind == KeyEventKind::Press
+                    && !handle_key(key_event.code, monitors, &mut view)
+                {
+                    return Ok(());
                 }
             }
         }
 
         if last_tick.elapsed() >= tick_rate {
-            cpu_monitor.refresh();
-            gpu_monitor.refresh();
+            monitors
+                .process
+                .set_smaps_enabled(view.memory_column.needs_smaps());
+            monitors.process.set_occupancy_enabled(view.occupancy);
+            monitors.cgroups.set_enabled(view.screen == Screen::Cgroups);
+            monitors
+                .docker
+                .set_enabled(view.screen == Screen::Containers);
+            monitors.process.set_thread_focus(
+                view.selected_pid
+                    .filter(|_| view.expanded || view.highlight_cores),
+            );
+            monitors.refresh();
+            if let Some(result) = outputs.update(monitors) {
+                view.push_error = result.err().map(|err| err.to_string());
+            }
+            refresh_detail(monitors, &mut view);
             last_tick = Instant::now();
         }
     }
 }
+
+/// Apply one key press to the view. Returns `false` when the app should quit.
+fn handle_key(code: KeyCode, monitors: &Monitors, view: &mut ViewState) -> bool {
+    if let Some(edit) = view.editor.as_mut() {
+        match code {
+            KeyCode::F(10) => return false,
+            KeyCode::Esc => view.editor = None,
+            KeyCode::Up => edit.previous_field(),
+            KeyCode::Down => edit.next_field(),
+            KeyCode::Left => edit.adjust(-1),
+            KeyCode::Right => edit.adjust(1),
+            KeyCode::Char(' ') => edit.toggle_cpu(),
+            KeyCode::Char('a') | KeyCode::Char('A') => edit.select_all_cpus(),
+            KeyCode::Enter => edit.apply(),
+            _ => {}
+        }
+        return true;
+    }
+
+    match code {
+        KeyCode::F(10) | KeyCode::Char('q') | KeyCode::Char('Q') => return false,
+        KeyCode::Tab => view.screen = view.screen.next(),
+        KeyCode::Char('t') | KeyCode::Char('T') => view.group_by_topology = !view.group_by_topology,
+        KeyCode::Char('h') | KeyCode::Char('H') => view.heatmap = !view.heatmap,
+        KeyCode::Char('p') | KeyCode::Char('P') => view.cgroup_pressure = !view.cgroup_pressure,
+        KeyCode::Char('u') | KeyCode::Char('U') => view.units.bytes = view.units.bytes.toggled(),
+        KeyCode::Char('o') | KeyCode::Char('O') => view.occupancy = !view.occupancy,
+        KeyCode::Char('c') | KeyCode::Char('C') => view.highlight_cores = !view.highlight_cores,
+        KeyCode::Char('f') | KeyCode::Char('F') => {
+            view.units.temperature = view.units.temperature.toggled()
+        }
+        _ if view.screen == Screen::Processes => handle_process_key(code, monitors, view),
+        _ if view.screen == Screen::Cgroups => handle_cgroup_key(code, monitors, view),
+        _ => {}
+    }
+    true
+}
+
+fn handle_process_key(code: KeyCode, monitors: &Monitors, view: &mut ViewState) {
+    match code {
+        KeyCode::Char('s') | KeyCode::Char('S') => view.sort = view.sort.next_for(view.group_by),
+        KeyCode::Char('m') | KeyCode::Char('M') => view.memory_column = view.memory_column.next(),
+        KeyCode::Char('g') | KeyCode::Char('G') => {
+            view.group_by = view.group_by.next();
+            if view.group_by != GroupBy::None && view.sort == SortKey::Network {
+                view.sort = SortKey::default();
+            }
+        }
+        KeyCode::Char('l') | KeyCode::Char('L') => view.event_log = !view.event_log,
+        KeyCode::Char('[') => view.event_scroll = view.event_scroll.saturating_sub(1),
+        KeyCode::Char(']') => {
+            let events = monitors.process.lifecycle().events().len();
+            view.event_scroll = view.event_scroll.saturating_add(1).min(last_line(events));
+        }
+        // Grouped rows have no single process to act on.
+        _ if view.group_by != GroupBy::None => {}
+        KeyCode::Up => move_selection(monitors, view, -1),
+        KeyCode::Down => move_selection(monitors, view, 1),
+        KeyCode::Enter => {
+            if let Some(pid) = view.selected_pid {
+                view.detail = ProcessDetail::load(pid, monitors.process.clock());
+                view.detail_exited = false;
+                view.detail_scroll = 0;
+            }
+        }
+        KeyCode::Char('e') | KeyCode::Char('E') => {
+            if let Some(pid) = view.selected_pid {
+                match SchedulingEdit::load(pid, monitors.cpu.cpus()) {
+                    Ok(edit) => {
+                        view.editor = Some(edit);
+                        view.notice = None;
+                    }
+                    Err(message) => view.notice = Some(message),
+                }
+            }
+        }
+        KeyCode::Esc => {
+            view.notice = None;
+            view.detail = None;
+            view.detail_exited = false;
+        }
+        KeyCode::Char('x') | KeyCode::Char('X') => view.expanded = !view.expanded,
+        KeyCode::PageUp => {
+            view.detail_scroll = view
+                .detail_scroll
+                .min(view.detail_max_scroll.get())
+                .saturating_sub(10)
+        }
+        KeyCode::PageDown => {
+            view.detail_scroll = view
+                .detail_scroll
+                .saturating_add(10)
+                .min(view.detail_max_scroll.get());
+        }
+        _ => {}
+    }
+}
+
+fn handle_cgroup_key(code: KeyCode, monitors: &Monitors, view: &mut ViewState) {
+    let nodes = tree::visible(monitors.cgroups.nodes(), &view.cgroup_expanded);
+    if nodes.is_empty() {
+        return;
+    }
+    let index = view
+        .cgroup_selected
+        .as_ref()
+        .and_then(|path| nodes.iter().position(|node| &node.path == path));
+    let Some(index) = index else {
+        view.cgroup_selected = Some(nodes[0].path.clone());
+        return;
+    };
+    let node = nodes[index];
+    match code {
+        KeyCode::Up => view.cgroup_selected = Some(nodes[index.saturating_sub(1)].path.clone()),
+        KeyCode::Down => {
+            view.cgroup_selected = Some(nodes[(index + 1).min(nodes.len() - 1)].path.clone())
+        }
+        KeyCode::Right | KeyCode::Enter if node.has_children => {
+            view.cgroup_expanded.insert(node.path.clone());
+        }
+        KeyCode::Left if view.cgroup_expanded.contains(&node.path) => {
+            view.cgroup_expanded.remove(&node.path);
+        }
+        KeyCode::Left => {
+            if let Some(parent) = nodes[..index].iter().rev().find(|n| n.depth < node.depth) {
+                view.cgroup_selected = Some(parent.path.clone());
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Move the selected process by `step` rows, tracking it by pid across re-sorts.
+fn move_selection(monitors: &Monitors, view: &mut ViewState, step: isize) {
+    let processes = process::sorted(monitors.process.processes(), view.sort, view.memory_column);
+    if processes.is_empty() {
+        view.selected_pid = None;
+        return;
+    }
+    let next = match view
+        .selected_pid
+        .and_then(|pid| processes.iter().position(|p| p.pid == pid))
+    {
+        Some(index) => index.saturating_add_signed(step).min(processes.len() - 1),
+        None => 0,
+    };
+    view.selected_pid = Some(processes[next].pid);
+}
+
+/// Largest scroll offset that still shows the last of `lines` lines.
+fn last_line(lines: usize) -> u16 {
+    u16::try_from(lines.saturating_sub(1)).unwrap_or(u16::MAX)
+}
+
+/// Reload the open detail pane; keep the last snapshot once the process exits.
+fn refresh_detail(monitors: &Monitors, view: &mut ViewState) {
+    let Some(pid) = view.detail.as_ref().map(|detail| detail.pid) else {
+        return;
+    };
+    if view.detail_exited {
+        return;
+    }
+    match ProcessDetail::load(pid, monitors.process.clock()) {
+        Some(detail) => view.detail = Some(detail),
+        None => view.detail_exited = true,
+    }
+}
@@ -0,0 +1,88 @@
+//! cgroup v2 discovery helpers.
+
+pub mod tree;
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Mount point of the cgroup v2 hierarchy, handling hybrid v1/v2 setups.
+pub fn cgroup_root() -> Option<PathBuf> {
+    let unified = Path::new("/sys/fs/cgroup");
+    if unified.join("cgroup.controllers").exists() {
+        return Some(unified.to_path_buf());
+    }
+    let hybrid = unified.join("unified");
+    hybrid.exists().then_some(hybrid)
+}
+
+/// Directory of the cgroup v2 group that `pid` belongs to (`self` for rustop itself).
+pub fn process_cgroup_dir(pid: &str) -> Option<PathBuf> {
+    let root = cgroup_root()?;
+    let text = fs::read_to_string(format!("/proc/{pid}/cgroup")).ok()?;
+    let path = parse_proc_cgroup(&text)?;
+    Some(root.join(path.trim_start_matches('/')))
+}
+
+/// Extract the unified (v2) hierarchy path from a `/proc/<pid>/cgroup` file.
+pub fn parse_proc_cgroup(text: &str) -> Option<&str> {
+    text.lines().find_map(|line| line.strip_prefix("0::"))
+}
+
+/// The innermost systemd slice of a cgroup path, or the whole path outside systemd.
+pub fn slice_of(path: &str) -> &str {
+    path.split('/')
+        .rev()
+        .find(|component| component.ends_with(".slice"))
+        .unwrap_or(path)
+}
+
+/// The systemd unit managed by PID 1 that a cgroup path belongs to: the
+/// outermost `.service` or `.scope`, so user services roll up into their
+/// `user@<uid>.service`. Falls back to the innermost slice.
+pub fn unit_of(path: &str) -> &str {
+    path.split('/')
+        .find(|component| component.ends_with(".service") || component.ends_with(".scope"))
+        .unwrap_or_else(|| slice_of(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_proc_cgroup_finds_unified_entry() {
+        let text = "4:memory:/job\n0::/user.slice/user-1000.slice/session-2.scope\n";
+        assert_eq!(
+            parse_proc_cgroup(text),
+            Some("/user.slice/user-1000.slice/session-2.scope")
+        );
+        assert_eq!(parse_proc_cgroup("4:memory:/job\n"), None);
+    }
+
+    #[test]
+    fn slice_of_picks_innermost_slice() {
+        assert_eq!(
+            slice_of("/user.slice/user-1000.slice/session-2.scope"),
+            "user-1000.slice"
+        );
+        assert_eq!(slice_of("/system.slice/nginx.service"), "system.slice");
+        assert_eq!(slice_of("/docker/abc123"), "/docker/abc123");
+    }
+
+    #[test]
+    fn unit_of_picks_system_unit() {
+        assert_eq!(unit_of("/system.slice/nginx.service"), "nginx.service");
+        assert_eq!(
+            unit_of("/user.slice/user-1000.slice/user@1000.service/app.slice/foot.service"),
+            "user@1000.service"
+        );
+        assert_eq!(
+            unit_of("/user.slice/user-1000.slice/session-2.scope"),
+            "session-2.scope"
+        );
+        assert_eq!(unit_of("/init.scope"), "init.scope");
+        assert_eq!(unit_of("/system.slice"), "system.slice");
+    }
+}
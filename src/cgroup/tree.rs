@@ -0,0 +1,323 @@
+//! Per-group resource usage across the whole cgroup v2 hierarchy.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use crate::{
+    cgroup,
+    pressure::{self, Pressure, Resource},
+    units::{ByteRate, Bytes},
+};
+
+/// Usage of one cgroup, listed in depth-first order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CgroupNode {
+    /// Path relative to the hierarchy root, `/` for the root itself.
+    pub path: String,
+    pub name: String,
+    pub depth: usize,
+    pub has_children: bool,
+    /// CPU usage as a percentage of one CPU, from `cpu.stat` deltas.
+    pub cpu_percent: Option<f32>,
+    pub memory_current: Option<Bytes>,
+    /// `memory.max`, `None` when unlimited or not exposed.
+    pub memory_max: Option<Bytes>,
+    /// Throughput summed over every device in `io.stat`.
+    pub read_rate: Option<ByteRate>,
+    pub write_rate: Option<ByteRate>,
+    pub pids: Option<u64>,
+    /// Pressure per resource, indexed like [`Resource::ALL`].
+    pub pressure: [Option<Pressure>; 3],
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Counters {
+    cpu_usec: Option<u64>,
+    io: Option<IoTotals>,
+}
+
+/// Bytes read and written across all devices.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct IoTotals {
+    pub read_bytes: u64,
+    pub write_bytes: u64,
+}
+
+/// Walks the cgroup v2 tree while the cgroup screen is open.
+pub struct CgroupMonitor {
+    root: Option<PathBuf>,
+    enabled: bool,
+    nodes: Vec<CgroupNode>,
+    previous: HashMap<String, Counters>,
+    last_refresh: Option<Instant>,
+}
+
+impl CgroupMonitor {
+    /// Construct a new monitor and perform an initial refresh.
+    pub fn new() -> Self {
+        let mut monitor = Self {
+            root: cgroup::cgroup_root(),
+            enabled: false,
+            nodes: Vec::new(),
+            previous: HashMap::new(),
+            last_refresh: None,
+        };
+        monitor.refresh();
+        monitor
+    }
+
+    /// Enable or disable walking the hierarchy on refresh.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        if self.enabled != enabled {
+            self.enabled = enabled;
+            self.nodes.clear();
+            self.previous.clear();
+            self.last_refresh = None;
+        }
+    }
+
+    /// Re-read every cgroup and compute rates since the previous walk.
+    pub fn refresh(&mut self) {
+        let Some(root) = self.root.as_ref().filter(|_| self.enabled) else {
+            return;
+        };
+        let now = Instant::now();
+        let elapsed = self
+            .last_refresh
+            .map(|at| now.duration_since(at).as_secs_f64())
+            .unwrap_or(0.0);
+
+        let mut previous = HashMap::with_capacity(self.previous.len());
+        self.nodes = walk(root)
+            .into_iter()
+            .map(|(path, name, depth, has_children)| {
+                let dir = root.join(path.trim_start_matches('/'));
+                let read = |file: &str| fs::read_to_string(dir.join(file)).ok();
+                let counters = Counters {
+                    cpu_usec: read("cpu.stat").and_then(|text| parse_cpu_usage(&text)),
+                    io: read("io.stat").map(|text| parse_io_stat(&text)),
+                };
+                let before = self.previous.get(&path).copied().unwrap_or_default();
+                let cpu_percent = match (before.cpu_usec, counters.cpu_usec) {
+                    (Some(before), Some(after)) if elapsed > 0.0 => {
+                        Some((after.saturating_sub(before) as f64 / elapsed / 1e4) as f32)
+                    }
+                    _ => None,
+                };
+                let (read_rate, write_rate) = match (before.io, counters.io) {
+                    (Some(before), Some(after)) => (
+                        ByteRate::between(
+                            Bytes(before.read_bytes),
+                            Bytes(after.read_bytes),
+                            elapsed,
+                        ),
+                        ByteRate::between(
+                            Bytes(before.write_bytes),
+                            Bytes(after.write_bytes),
+                            elapsed,
+                        ),
+                    ),
+                    _ => (None, None),
+                };
+                previous.insert(path.clone(), counters);
+
+                CgroupNode {
+                    cpu_percent,
+                    memory_current: read("memory.current")
+                        .and_then(|text| parse_limit(&text))
+                        .map(Bytes),
+                    memory_max: read("memory.max")
+                        .and_then(|text| parse_limit(&text))
+                        .map(Bytes),
+                    read_rate,
+                    write_rate,
+                    pids: read("pids.current").and_then(|text| parse_limit(&text)),
+                    pressure: Resource::ALL.map(|r| {
+                        read(&format!("{}.pressure", r.name()))
+                            .map(|text| pressure::parse_pressure(&text))
+                    }),
+                    path,
+                    name,
+                    depth,
+                    has_children,
+                }
+            })
+            .collect();
+        self.previous = previous;
+        self.last_refresh = Some(now);
+    }
+
+    /// Mount point of the hierarchy, if any.
+    pub fn root(&self) -> Option<&Path> {
+        self.root.as_deref()
+    }
+
+    /// Every cgroup from the latest walk, depth-first with siblings by name.
+    pub fn nodes(&self) -> &[CgroupNode] {
+        &self.nodes
+    }
+}
+
+/// Nodes shown when only the groups in `expanded` (and the root) list their children.
+pub fn visible<'a>(nodes: &'a [CgroupNode], expanded: &HashSet<String>) -> Vec<&'a CgroupNode> {
+    let mut collapsed_at: Option<usize> = None;
+    nodes
+        .iter()
+        .filter(|node| {
+            if let Some(depth) = collapsed_at {
+                if node.depth > depth {
+                    return false;
+                }
+                collapsed_at = None;
+            }
+            if node.depth > 0 && !expanded.contains(&node.path) {
+                collapsed_at = Some(node.depth);
+            }
+            true
+        })
+        .collect()
+}
+
+/// `(path, name, depth, has_children)` for `root` and every group below it.
+fn walk(root: &Path) -> Vec<(String, String, usize, bool)> {
+    fn children(dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = fs::read_dir(dir)
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .filter(|entry| entry.file_type().is_ok_and(|t| t.is_dir()))
+                    .filter_map(|entry| entry.file_name().into_string().ok())
+                    .collect()
+            })
+            .unwrap_or_default();
+        names.sort();
+        names
+    }
+    fn visit(
+        dir: &Path,
+        path: String,
+        name: String,
+        depth: usize,
+        out: &mut Vec<(String, String, usize, bool)>,
+    ) {
+        let names = children(dir);
+        out.push((path.clone(), name, depth, !names.is_empty()));
+        for child in names {
+            let child_path = if path == "/" {
+                format!("/{child}")
+            } else {
+                format!("{path}/{child}")
+            };
+            visit(&dir.join(&child), child_path, child, depth + 1, out);
+        }
+    }
+
+    let mut out = Vec::new();
+    visit(root, "/".to_string(), "/".to_string(), 0, &mut out);
+    out
+}
+
+/// Cumulative CPU time in microseconds from `cpu.stat`.
+pub fn parse_cpu_usage(text: &str) -> Option<u64> {
+    text.lines()
+        .find_map(|line| line.strip_prefix("usage_usec "))
+        .and_then(|value| value.trim().parse().ok())
+}
+
+/// Sum `rbytes`/`wbytes` over every device line of `io.stat`.
+pub fn parse_io_stat(text: &str) -> IoTotals {
+    let mut totals = IoTotals::default();
+    for field in text.split_whitespace() {
+        if let Some(value) = field.strip_prefix("rbytes=") {
+            totals.read_bytes += value.parse().unwrap_or(0);
+        } else if let Some(value) = field.strip_prefix("wbytes=") {
+            totals.write_bytes += value.parse().unwrap_or(0);
+        }
+    }
+    totals
+}
+
+/// A single-number cgroup file, `None` for `max` or unparsable content.
+pub fn parse_limit(text: &str) -> Option<u64> {
+    text.trim().parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDir;
+
+    #[test]
+    fn parses_cgroup_files() {
+        assert_eq!(
+            parse_cpu_usage("usage_usec 1500\nuser_usec 1000\nsystem_usec 500\n"),
+            Some(1500)
+        );
+        assert_eq!(
+            parse_io_stat(
+                "8:0 rbytes=100 wbytes=20 rios=1 wios=1 dbytes=0 dios=0\n\
+                 8:16 rbytes=50 wbytes=5 rios=1 wios=1 dbytes=0 dios=0\n"
+            ),
+            IoTotals {
+                read_bytes: 150,
+                write_bytes: 25
+            }
+        );
+        assert_eq!(parse_limit("max\n"), None);
+        assert_eq!(parse_limit("4096\n"), Some(4096));
+    }
+
+    #[test]
+    fn walks_fake_hierarchy_and_hides_collapsed_groups() {
+        let root = TempDir::new("cgroup");
+        fs::create_dir_all(root.path().join("system.slice/nginx.service")).unwrap();
+        fs::create_dir_all(root.path().join("user.slice")).unwrap();
+        root.write("system.slice/memory.current", "2048\n");
+        root.write("system.slice/memory.max", "max\n");
+        root.write("system.slice/pids.current", "7\n");
+
+        let mut monitor = CgroupMonitor {
+            root: Some(root.path().to_path_buf()),
+            enabled: true,
+            nodes: Vec::new(),
+            previous: HashMap::new(),
+            last_refresh: None,
+        };
+        monitor.refresh();
+
+        let paths: Vec<&str> = monitor.nodes().iter().map(|n| n.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                "/",
+                "/system.slice",
+                "/system.slice/nginx.service",
+                "/user.slice"
+            ]
+        );
+        let slice = &monitor.nodes()[1];
+        assert_eq!(slice.depth, 1);
+        assert!(slice.has_children);
+        assert_eq!(slice.memory_current, Some(Bytes(2048)));
+        assert_eq!(slice.memory_max, None);
+        assert_eq!(slice.pids, Some(7));
+
+        let mut expanded = HashSet::new();
+        let shown = |expanded: &HashSet<String>| {
+            visible(monitor.nodes(), expanded)
+                .iter()
+                .map(|n| n.name.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(shown(&expanded), vec!["/", "system.slice", "user.slice"]);
+        expanded.insert("/system.slice".to_string());
+        assert_eq!(
+            shown(&expanded),
+            vec!["/", "system.slice", "nginx.service", "user.slice"]
+        );
+    }
+}
@@ -0,0 +1,542 @@
+//! Command-line argument parsing.
+
+use std::{
+    net::{SocketAddr, ToSocketAddrs},
+    path::PathBuf,
+    time::Duration,
+};
+
+use crate::units::{ByteUnits, DisplayUnits, TemperatureUnit};
+
+/// Usage text printed for `--help` and on invalid arguments.
+pub const USAGE: &str = "\
+Usage: rustop [OPTIONS]
+       rustop snapshot
+       rustop record [RECORD OPTIONS]
+       rustop serve [--prometheus <ADDR>] [--influx <TARGET>] [--statsd <ADDR>]
+
+Commands:
+  snapshot       Print one JSON snapshot of every metric and exit (same as --json)
+  record         Write one timestamped record per sample until interrupted
+  serve          Run only the metrics endpoint and push outputs, without the TUI
+
+Record options:
+  --format <ndjson|csv>      Output format (default ndjson)
+  --interval <DURATION>      Time between samples, e.g. 500ms, 1s, 2m (default 1s)
+  --output <FILE>            File to write instead of stdout
+  --rotate-size <SIZE>       Start a new file once the current one reaches SIZE, e.g. 100M
+  --rotate-every <DURATION>  Start a new file after DURATION, e.g. 1h
+
+Options:
+  --json                     Print one JSON snapshot of every metric and exit
+  --si                       Show sizes in SI units (kB, MB, GB) instead of IEC (KiB, MiB, GiB)
+  --fahrenheit               Show temperatures in degrees Fahrenheit
+  --d-state-secs <SECS>      Warn about processes in uninterruptible sleep for longer (default 10)
+
+Metrics outputs:
+  --prometheus <ADDR>        Serve Prometheus metrics at http://ADDR/metrics, e.g. 127.0.0.1:9500
+  --influx <TARGET>          Push InfluxDB line protocol to a file, `-` for stdout (serve only),
+                             or http://host:port/path, e.g. http://127.0.0.1:8086/write?db=rustop
+  --statsd <ADDR>            Send StatsD gauges over UDP, e.g. 127.0.0.1:8125
+  --statsd-prefix <PREFIX>   Metric name prefix (default rustop)
+  --statsd-tag <KEY:VALUE>   Add a DogStatsD tag to every gauge; may be repeated
+  --push-interval <DURATION> Time between pushes (default 10s)
+  -h, --help                 Print this help";
+
+/// Options shared by every mode.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Options {
+    pub units: DisplayUnits,
+    /// Address of the Prometheus `/metrics` endpoint, when enabled.
+    pub prometheus: Option<SocketAddr>,
+    pub influx: Option<InfluxTarget>,
+    pub statsd: Option<StatsdOptions>,
+    /// Time between pushes; the push module's default when absent.
+    pub push_interval: Option<Duration>,
+    /// Seconds in `D` state before a process is flagged; the anomaly
+    /// detector's default when absent.
+    pub d_state_secs: Option<u64>,
+}
+
+impl Options {
+    /// Whether any metrics output is configured.
+    pub fn has_outputs(&self) -> bool {
+        self.prometheus.is_some() || self.influx.is_some() || self.statsd.is_some()
+    }
+}
+
+/// Where InfluxDB line protocol is written.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InfluxTarget {
+    Stdout,
+    /// Appended to, so several runs can share one file.
+    File(PathBuf),
+    /// POSTed to `path` on `addr` (`host:port`), e.g. InfluxDB's `/write?db=rustop`.
+    Http {
+        addr: String,
+        path: String,
+    },
+}
+
+impl InfluxTarget {
+    /// Parse `-`, `http://host:port/path` or a file path.
+    pub fn parse(target: &str) -> Result<Self, String> {
+        if target == "-" {
+            return Ok(InfluxTarget::Stdout);
+        }
+        if target.starts_with("https://") {
+            return Err("--influx does not support https; push through a local agent".to_string());
+        }
+        let Some(rest) = target.strip_prefix("http://") else {
+            return Ok(InfluxTarget::File(PathBuf::from(target)));
+        };
+        let (addr, path) = match rest.find('/') {
+            Some(slash) => (&rest[..slash], &rest[slash..]),
+            None => (rest, "/write"),
+        };
+        if addr.is_empty() {
+            return Err(format!(
+                "--influx expects http://host:port/path, got {target}"
+            ));
+        }
+        let addr = if addr.contains(':') {
+            addr.to_string()
+        } else {
+            format!("{addr}:80")
+        };
+        Ok(InfluxTarget::Http {
+            addr,
+            path: path.to_string(),
+        })
+    }
+}
+
+/// StatsD destination and naming.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatsdOptions {
+    pub addr: SocketAddr,
+    /// Prepended to every metric name with a `.`; empty for none.
+    pub prefix: String,
+    /// DogStatsD `key:value` tags added to every gauge.
+    pub tags: Vec<String>,
+}
+
+/// File format written by `rustop record`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RecordFormat {
+    /// One JSON object per line.
+    #[default]
+    Ndjson,
+    /// A header row followed by one row per sample.
+    Csv,
+}
+
+/// Settings for `rustop record`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordOptions {
+    pub format: RecordFormat,
+    pub interval: Duration,
+    /// Destination file; stdout when absent.
+    pub output: Option<PathBuf>,
+    /// Rotate once the current file reaches this many bytes.
+    pub rotate_size: Option<u64>,
+    /// Rotate once the current file has been open this long.
+    pub rotate_every: Option<Duration>,
+}
+
+impl Default for RecordOptions {
+    fn default() -> Self {
+        Self {
+            format: RecordFormat::default(),
+            interval: Duration::from_secs(1),
+            output: None,
+            rotate_size: None,
+            rotate_every: None,
+        }
+    }
+}
+
+/// What rustop was asked to do.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    /// Run the interactive TUI.
+    Tui(Options),
+    /// Print a JSON snapshot and exit.
+    Snapshot,
+    /// Log samples to a file or stdout.
+    Record(RecordOptions),
+    /// Serve and push metrics without the TUI.
+    Serve(Options),
+    /// Print usage and exit.
+    Help,
+}
+
+/// Flags that only apply to `rustop record`.
+const RECORD_FLAGS: [&str; 5] = [
+    "--format",
+    "--interval",
+    "--output",
+    "--rotate-size",
+    "--rotate-every",
+];
+
+/// Metrics output flags, which apply to the TUI and `rustop serve` only.
+const OUTPUT_FLAGS: [&str; 6] = [
+    "--prometheus",
+    "--influx",
+    "--statsd",
+    "--statsd-prefix",
+    "--statsd-tag",
+    "--push-interval",
+];
+
+/// Parse command-line arguments (excluding the program name).
+pub fn parse_args<I>(args: I) -> Result<Command, String>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut options = Options::default();
+    let mut snapshot = false;
+    let mut record = false;
+    let mut serve = false;
+    let mut statsd_addr = None;
+    let mut statsd_prefix = "rustop".to_string();
+    let mut statsd_tags = Vec::new();
+    let mut record_options = RecordOptions::default();
+    let mut record_flag = None;
+    let mut output_flag = None;
+    let mut tui_flag = None;
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if RECORD_FLAGS.contains(&arg.as_str()) {
+            record_flag.get_or_insert_with(|| arg.clone());
+        }
+        if OUTPUT_FLAGS.contains(&arg.as_str()) {
+            output_flag.get_or_insert_with(|| arg.clone());
+        }
+        if arg == "--d-state-secs" {
+            tui_flag.get_or_insert_with(|| arg.clone());
+        }
+        let mut value = || args.next().ok_or_else(|| format!("{arg} requires a value"));
+        match arg.as_str() {
+            "snapshot" | "--json" => snapshot = true,
+            "record" => record = true,
+            "serve" => serve = true,
+            "--prometheus" => options.prometheus = Some(socket_addr(&arg, &value()?)?),
+            "--influx" => options.influx = Some(InfluxTarget::parse(&value()?)?),
+            "--statsd" => statsd_addr = Some(socket_addr(&arg, &value()?)?),
+            "--statsd-prefix" => statsd_prefix = value()?,
+            "--statsd-tag" => {
+                let tag = value()?;
+                if !tag.contains(':') {
+                    return Err(format!("--statsd-tag expects key:value, got {tag}"));
+                }
+                statsd_tags.push(tag);
+            }
+            "--push-interval" => {
+                options.push_interval = Some(
+                    parse_duration(&value()?)
+                        .filter(|interval| !interval.is_zero())
+                        .ok_or("--push-interval expects a duration such as 10s or 1m")?,
+                )
+            }
+            "--d-state-secs" => {
+                options.d_state_secs = Some(
+                    value()?
+                        .parse()
+                        .map_err(|_| "--d-state-secs expects a number of seconds")?,
+                )
+            }
+            "--si" => options.units.bytes = ByteUnits::Si,
+            "--fahrenheit" => options.units.temperature = TemperatureUnit::Fahrenheit,
+            "--format" => {
+                record_options.format = match value()?.as_str() {
+                    "ndjson" => RecordFormat::Ndjson,
+                    "csv" => RecordFormat::Csv,
+                    other => return Err(format!("unknown format: {other}")),
+                }
+            }
+            "--interval" => {
+                record_options.interval = parse_duration(&value()?)
+                    .filter(|interval| !interval.is_zero())
+                    .ok_or("--interval expects a duration such as 500ms, 1s or 2m")?
+            }
+            "--output" => record_options.output = Some(PathBuf::from(value()?)),
+            "--rotate-size" => {
+                record_options.rotate_size = Some(
+                    parse_size(&value()?)
+                        .ok_or("--rotate-size expects a size such as 10M or 1G")?,
+                )
+            }
+            "--rotate-every" => {
+                record_options.rotate_every = Some(
+                    parse_duration(&value()?)
+                        .ok_or("--rotate-every expects a duration such as 30m or 1h")?,
+                )
+            }
+            "-h" | "--help" => return Ok(Command::Help),
+            other => return Err(format!("unknown argument: {other}")),
+        }
+    }
+    if let Some(flag) = output_flag.filter(|_| record || snapshot) {
+        let command = if record { "record" } else { "snapshot" };
+        return Err(format!("{flag} is not valid with {command}"));
+    }
+    if let Some(flag) = tui_flag.filter(|_| record || snapshot || serve) {
+        let command = if record {
+            "record"
+        } else if snapshot {
+            "snapshot"
+        } else {
+            "serve"
+        };
+        return Err(format!("{flag} is not valid with {command}"));
+    }
+    if record {
+        if record_options.output.is_none()
+            && (record_options.rotate_size.is_some() || record_options.rotate_every.is_some())
+        {
+            return Err("rotation requires --output".to_string());
+        }
+        return Ok(Command::Record(record_options));
+    }
+    match statsd_addr {
+        Some(addr) => {
+            options.statsd = Some(StatsdOptions {
+                addr,
+                prefix: statsd_prefix,
+                tags: statsd_tags,
+            })
+        }
+        None if !statsd_tags.is_empty() || statsd_prefix != "rustop" => {
+            return Err("--statsd-prefix and --statsd-tag require --statsd".to_string())
+        }
+        None => {}
+    }
+    if serve {
+        if !options.has_outputs() {
+            return Err("serve requires --prometheus, --influx or --statsd".to_string());
+        }
+        return Ok(Command::Serve(options));
+    }
+    if options.influx == Some(InfluxTarget::Stdout) {
+        return Err("--influx - writes to stdout and is only valid with serve".to_string());
+    }
+    if let Some(flag) = record_flag {
+        return Err(format!("{flag} is only valid with record"));
+    }
+    Ok(if snapshot {
+        Command::Snapshot
+    } else {
+        Command::Tui(options)
+    })
+}
+
+fn socket_addr(flag: &str, addr: &str) -> Result<SocketAddr, String> {
+    addr.to_socket_addrs()
+        .ok()
+        .and_then(|mut addrs| addrs.next())
+        .ok_or_else(|| format!("{flag} expects host:port, got {addr}"))
+}
+
+/// Parse a duration such as `250ms`, `1s`, `5m`, `2h`, or plain seconds.
+pub fn parse_duration(text: &str) -> Option<Duration> {
+    let split = text
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let number: u64 = number.parse().ok()?;
+    let millis = match unit {
+        "ms" => 1,
+        "" | "s" => 1_000,
+        "m" => 60_000,
+        "h" => 3_600_000,
+        _ => return None,
+    };
+    Some(Duration::from_millis(number.checked_mul(millis)?))
+}
+
+/// Parse a size such as `512K`, `100M`, `1G` (binary multiples), or plain bytes.
+pub fn parse_size(text: &str) -> Option<u64> {
+    let split = text
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let number: u64 = number.parse().ok()?;
+    let shift = match unit.trim_end_matches(['B', 'b', 'i']) {
+        "" => 0,
+        "K" | "k" => 10,
+        "M" | "m" => 20,
+        "G" | "g" => 30,
+        _ => return None,
+    };
+    number.checked_mul(1 << shift).filter(|size| *size > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Command, String> {
+        parse_args(args.iter().map(|a| a.to_string()))
+    }
+
+    #[test]
+    fn parse_args_defaults_to_tui_with_iec() {
+        assert_eq!(parse(&[]), Ok(Command::Tui(Options::default())));
+    }
+
+    #[test]
+    fn parse_args_reads_unit_flags() {
+        let Ok(Command::Tui(options)) = parse(&["--si", "--fahrenheit"]) else {
+            panic!("expected TUI command");
+        };
+        assert_eq!(options.units.bytes, ByteUnits::Si);
+        assert_eq!(options.units.temperature, TemperatureUnit::Fahrenheit);
+    }
+
+    #[test]
+    fn parse_args_rejects_unknown_and_handles_help() {
+        assert_eq!(parse(&["--help"]), Ok(Command::Help));
+        assert_eq!(parse(&["--json"]), Ok(Command::Snapshot));
+        let Ok(Command::Serve(options)) = parse(&["serve", "--prometheus", "127.0.0.1:9500"])
+        else {
+            panic!("expected serve command");
+        };
+        assert_eq!(options.prometheus, Some("127.0.0.1:9500".parse().unwrap()));
+        assert_eq!(
+            parse(&["serve"]),
+            Err("serve requires --prometheus, --influx or --statsd".to_string())
+        );
+        let Ok(Command::Tui(options)) = parse(&["--prometheus", "127.0.0.1:9500"]) else {
+            panic!("expected TUI command");
+        };
+        assert!(options.prometheus.is_some());
+        assert_eq!(parse(&["snapshot", "--si"]), Ok(Command::Snapshot));
+        assert_eq!(
+            parse(&["--interval", "1s"]),
+            Err("--interval is only valid with record".to_string())
+        );
+        assert_eq!(
+            parse(&["--bogus"]),
+            Err("unknown argument: --bogus".to_string())
+        );
+    }
+
+    #[test]
+    fn parse_args_reads_record_options() {
+        assert_eq!(
+            parse(&["record"]),
+            Ok(Command::Record(RecordOptions::default()))
+        );
+        let Ok(Command::Record(options)) = parse(&[
+            "record",
+            "--format",
+            "csv",
+            "--interval",
+            "250ms",
+            "--output",
+            "run.csv",
+            "--rotate-size",
+            "10M",
+            "--rotate-every",
+            "1h",
+        ]) else {
+            panic!("expected record command");
+        };
+        assert_eq!(options.format, RecordFormat::Csv);
+        assert_eq!(options.interval, Duration::from_millis(250));
+        assert_eq!(options.output, Some(PathBuf::from("run.csv")));
+        assert_eq!(options.rotate_size, Some(10 << 20));
+        assert_eq!(options.rotate_every, Some(Duration::from_secs(3600)));
+
+        assert_eq!(
+            parse(&["record", "--rotate-size", "1G"]),
+            Err("rotation requires --output".to_string())
+        );
+        assert_eq!(
+            parse(&["record", "--format", "xml"]),
+            Err("unknown format: xml".to_string())
+        );
+        assert_eq!(
+            parse(&["record", "--interval"]),
+            Err("--interval requires a value".to_string())
+        );
+        assert_eq!(
+            parse(&["record", "--prometheus", "127.0.0.1:9500"]),
+            Err("--prometheus is not valid with record".to_string())
+        );
+        assert_eq!(
+            parse(&["snapshot", "--statsd", "127.0.0.1:8125"]),
+            Err("--statsd is not valid with snapshot".to_string())
+        );
+    }
+
+    #[test]
+    fn parse_args_reads_d_state_threshold() {
+        let Ok(Command::Tui(options)) = parse(&["--d-state-secs", "30"]) else {
+            panic!("expected TUI command");
+        };
+        assert_eq!(options.d_state_secs, Some(30));
+        assert!(parse(&["--d-state-secs", "10s"]).is_err());
+        assert_eq!(
+            parse(&[
+                "serve",
+                "--prometheus",
+                "127.0.0.1:9500",
+                "--d-state-secs",
+                "5"
+            ]),
+            Err("--d-state-secs is not valid with serve".to_string())
+        );
+    }
+
+    #[test]
+    fn parse_duration_and_size_accept_suffixes() {
+        assert_eq!(parse_duration("2m"), Some(Duration::from_secs(120)));
+        assert_eq!(parse_duration("5"), Some(Duration::from_secs(5)));
+        assert_eq!(parse_duration("1d"), None);
+        assert_eq!(parse_size("512KiB"), Some(512 << 10));
+        assert_eq!(parse_size("4096"), Some(4096));
+        assert_eq!(parse_size("0"), None);
+    }
+
+    #[test]
+    fn parse_args_reads_push_outputs() {
+        let Ok(Command::Serve(options)) = parse(&[
+            "serve",
+            "--influx",
+            "http://127.0.0.1:8086/write?db=rustop",
+            "--statsd",
+            "127.0.0.1:8125",
+            "--statsd-prefix",
+            "lab",
+            "--statsd-tag",
+            "env:ci",
+            "--push-interval",
+            "5s",
+        ]) else {
+            panic!("expected serve command");
+        };
+        assert_eq!(
+            options.influx,
+            Some(InfluxTarget::Http {
+                addr: "127.0.0.1:8086".to_string(),
+                path: "/write?db=rustop".to_string()
+            })
+        );
+        let statsd = options.statsd.unwrap();
+        assert_eq!(statsd.addr, "127.0.0.1:8125".parse().unwrap());
+        assert_eq!(statsd.prefix, "lab");
+        assert_eq!(statsd.tags, vec!["env:ci".to_string()]);
+        assert_eq!(options.push_interval, Some(Duration::from_secs(5)));
+
+        assert_eq!(
+            InfluxTarget::parse("metrics.lp"),
+            Ok(InfluxTarget::File(PathBuf::from("metrics.lp")))
+        );
+        assert!(parse(&["--influx", "-"]).is_err());
+        assert!(parse(&["--statsd-tag", "env:ci"]).is_err());
+        assert!(parse(&["--influx", "https://example.com/write"]).is_err());
+    }
+}
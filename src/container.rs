@@ -0,0 +1,193 @@
+//! Detects the limits of the cgroup rustop runs in, so a container is shown
+//! against its own quota rather than the host's totals.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use crate::{
+    cgroup::{self, tree},
+    topology,
+    units::{ByteUnits, Bytes},
+};
+
+/// Limits of the enclosing cgroup and the state needed to scale usage to them.
+#[derive(Debug, Clone)]
+pub struct Container {
+    /// cgroup path from `/proc/self/cgroup`, `/` inside a cgroup namespace.
+    label: String,
+    dir: PathBuf,
+    cpu_quota: Option<f64>,
+    memory_max: Option<Bytes>,
+    cpuset: Option<Vec<usize>>,
+    previous_usage: Option<(Instant, u64)>,
+    cpu_percent: Option<f32>,
+}
+
+impl Container {
+    /// Limits of rustop's own cgroup, or `None` when nothing restricts it below
+    /// the host's `host_cpus` CPUs and full memory.
+    pub fn detect(host_cpus: usize) -> Option<Self> {
+        let root = cgroup::cgroup_root()?;
+        let dir = cgroup::process_cgroup_dir("self")?;
+        let label = fs::read_to_string("/proc/self/cgroup")
+            .ok()
+            .and_then(|text| cgroup::parse_proc_cgroup(&text).map(str::to_string))?;
+        Self::from_dir(label, &root, dir, host_cpus)
+    }
+
+    /// Read limits from `dir` and every ancestor up to `root`, keeping the tightest.
+    fn from_dir(label: String, root: &Path, dir: PathBuf, host_cpus: usize) -> Option<Self> {
+        let chain: Vec<&Path> = dir
+            .ancestors()
+            .take_while(|path| path.starts_with(root))
+            .collect();
+        let read = |path: &Path, file: &str| fs::read_to_string(path.join(file)).ok();
+        let cpu_quota = chain
+            .iter()
+            .filter_map(|path| read(path, "cpu.max").and_then(|text| parse_cpu_max(&text)))
+            .reduce(f64::min);
+        let memory_max = chain
+            .iter()
+            .filter_map(|path| read(path, "memory.max").and_then(|text| tree::parse_limit(&text)))
+            .min()
+            .map(Bytes);
+        let cpuset = read(&dir, "cpuset.cpus.effective")
+            .map(|text| topology::parse_cpu_list(&text))
+            .filter(|cpus| !cpus.is_empty() && cpus.len() < host_cpus);
+
+        if cpu_quota.is_none() && memory_max.is_none() && cpuset.is_none() {
+            return None;
+        }
+        Some(Self {
+            label,
+            dir,
+            cpu_quota,
+            memory_max,
+            cpuset,
+            previous_usage: None,
+            cpu_percent: None,
+        })
+    }
+
+    /// Sample the cgroup's CPU time and scale it to the quota.
+    pub fn refresh(&mut self) {
+        let Some(quota) = self.cpu_quota else {
+            return;
+        };
+        let now = Instant::now();
+        let usage = fs::read_to_string(self.dir.join("cpu.stat"))
+            .ok()
+            .and_then(|text| tree::parse_cpu_usage(&text));
+        self.cpu_percent = match (self.previous_usage, usage) {
+            (Some((at, before)), Some(after)) => {
+                let elapsed = now.duration_since(at).as_secs_f64();
+                (elapsed > 0.0).then(|| {
+                    (after.saturating_sub(before) as f64 / 1e6 / elapsed / quota * 100.0) as f32
+                })
+            }
+            _ => None,
+        };
+        self.previous_usage = usage.map(|usage| (now, usage));
+    }
+
+    /// CPU quota in CPUs (`cpu.max` quota over period).
+    pub fn cpu_quota(&self) -> Option<f64> {
+        self.cpu_quota
+    }
+
+    /// CPU usage as a percentage of the quota since the previous refresh.
+    pub fn cpu_percent(&self) -> Option<f32> {
+        self.cpu_percent
+    }
+
+    /// Tightest `memory.max` along the path to the root.
+    pub fn memory_max(&self) -> Option<Bytes> {
+        self.memory_max
+    }
+
+    /// Working set: `memory.current` minus reclaimable inactive file pages.
+    pub fn memory_used(&self) -> Option<Bytes> {
+        let current = fs::read_to_string(self.dir.join("memory.current"))
+            .ok()
+            .and_then(|text| tree::parse_limit(&text))?;
+        let inactive = fs::read_to_string(self.dir.join("memory.stat"))
+            .ok()
+            .and_then(|text| parse_inactive_file(&text))
+            .unwrap_or(0);
+        Some(Bytes(current.saturating_sub(inactive)))
+    }
+
+    /// CPUs from `cpuset.cpus.effective` when narrower than the host.
+    pub fn cpuset(&self) -> Option<&[usize]> {
+        self.cpuset.as_deref()
+    }
+
+    /// Banner line, e.g. `Container mode: /docker/ab12 (8.0 CPU quota, 32.0 GiB memory.max)`.
+    pub fn summary(&self, units: ByteUnits) -> String {
+        let mut limits = Vec::new();
+        if let Some(quota) = self.cpu_quota {
+            limits.push(format!("{quota:.1} CPU quota"));
+        }
+        if let Some(cpus) = &self.cpuset {
+            limits.push(format!("{} cpuset CPUs", cpus.len()));
+        }
+        if let Some(max) = self.memory_max {
+            limits.push(format!("{} memory.max", max.display(units)));
+        }
+        format!("Container mode: {} ({})", self.label, limits.join(", "))
+    }
+}
+
+/// Quota in CPUs from `cpu.max` (`<quota> <period>`), `None` when unlimited.
+pub fn parse_cpu_max(text: &str) -> Option<f64> {
+    let mut fields = text.split_whitespace();
+    let quota: f64 = fields.next()?.parse().ok()?;
+    let period: f64 = fields.next()?.parse().ok()?;
+    (period > 0.0).then(|| quota / period)
+}
+
+fn parse_inactive_file(text: &str) -> Option<u64> {
+    text.lines()
+        .find_map(|line| line.strip_prefix("inactive_file "))
+        .and_then(|value| value.trim().parse().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDir;
+
+    #[test]
+    fn parse_cpu_max_reads_quota() {
+        assert_eq!(parse_cpu_max("800000 100000\n"), Some(8.0));
+        assert_eq!(parse_cpu_max("max 100000\n"), None);
+        assert_eq!(parse_inactive_file("anon 10\ninactive_file 42\n"), Some(42));
+    }
+
+    #[test]
+    fn from_dir_keeps_tightest_limits() {
+        let root = TempDir::new("container");
+        let scope = "kubepods.slice/pod.slice/ctr.scope";
+        root.write("kubepods.slice/memory.max", "1024\n");
+        root.write(&format!("{scope}/memory.max"), "4096\n");
+        root.write(&format!("{scope}/cpu.max"), "200000 100000\n");
+        root.write(&format!("{scope}/cpuset.cpus.effective"), "0-1\n");
+        root.write(&format!("{scope}/memory.current"), "900\n");
+        root.write(&format!("{scope}/memory.stat"), "inactive_file 100\n");
+
+        let dir = root.path().join(scope);
+        let container = Container::from_dir("/ctr".into(), root.path(), dir, 8).unwrap();
+        let used = container.memory_used();
+        let unrestricted =
+            Container::from_dir("/".into(), root.path(), root.path().to_path_buf(), 8);
+
+        assert_eq!(container.cpu_quota(), Some(2.0));
+        assert_eq!(container.memory_max(), Some(Bytes(1024)));
+        assert_eq!(container.cpuset(), Some(&[0, 1][..]));
+        assert_eq!(used, Some(Bytes(800)));
+        assert!(unrestricted.is_none());
+    }
+}
@@ -1,26 +1,54 @@
 //! CPU monitoring helpers.
 
+use std::collections::VecDeque;
+
 use sysinfo::System;
 
+use crate::{
+    container::Container,
+    topology::CpuTopology,
+    units::{Bytes, Frequency},
+};
+
+/// Number of samples of per-thread history retained for the heatmap.
+pub const HISTORY_LEN: usize = 240;
+
 /// Captures CPU and memory metrics for display.
 pub struct CpuMonitor {
     system: System,
+    topology: CpuTopology,
+    container: Option<Container>,
     usages: Vec<f32>,
+    ids: Vec<usize>,
+    cpus: Vec<usize>,
+    history: Vec<VecDeque<f32>>,
     avg: f32,
-    memory_used: u64,
-    memory_total: u64,
+    frequency: Option<Frequency>,
+    memory_used: Bytes,
+    memory_total: Bytes,
+    swap_used: Bytes,
+    swap_total: Bytes,
 }
 
 impl CpuMonitor {
     /// Construct a new monitor and perform an initial refresh.
     pub fn new() -> Self {
         let system = System::new_all();
+        let container = Container::detect(system.cpus().len());
         let mut monitor = Self {
             system,
+            topology: CpuTopology::detect(),
+            container,
             usages: Vec::new(),
+            ids: Vec::new(),
+            cpus: Vec::new(),
+            history: Vec::new(),
             avg: 0.0,
-            memory_used: 0,
-            memory_total: 0,
+            frequency: None,
+            memory_used: Bytes(0),
+            memory_total: Bytes(0),
+            swap_used: Bytes(0),
+            swap_total: Bytes(0),
         };
         monitor.refresh();
         monitor
@@ -36,42 +64,150 @@ impl CpuMonitor {
             .iter()
             .map(|cpu| cpu.cpu_usage())
             .collect();
-        self.avg = if self.usages.is_empty() {
-            0.0
+        // sysinfo skips offline CPUs, so its indices are not kernel CPU ids.
+        self.ids = self
+            .system
+            .cpus()
+            .iter()
+            .enumerate()
+            .map(|(index, cpu)| cpu_id(cpu.name()).unwrap_or(index))
+            .collect();
+        self.history
+            .resize_with(self.usages.len(), || VecDeque::with_capacity(HISTORY_LEN));
+        for (history, usage) in self.history.iter_mut().zip(&self.usages) {
+            if history.len() == HISTORY_LEN {
+                history.pop_front();
+            }
+            history.push_back(*usage);
+        }
+        self.cpus = match self.container.as_ref().and_then(Container::cpuset) {
+            Some(cpuset) => cpuset
+                .iter()
+                .copied()
+                .filter(|id| self.ids.contains(id))
+                .collect(),
+            None => self.ids.clone(),
+        };
+        if let Some(container) = self.container.as_mut() {
+            container.refresh();
+        }
+        // Inside a CPU quota, usage is the cgroup's share of that quota.
+        self.avg = match self.container.as_ref().and_then(Container::cpu_percent) {
+            Some(percent) => percent,
+            None if self.cpus.is_empty() => 0.0,
+            None => {
+                self.cpus
+                    .iter()
+                    .filter_map(|&id| self.usage(id))
+                    .sum::<f32>()
+                    / self.cpus.len() as f32
+            }
+        };
+
+        let cpus = self.system.cpus();
+        self.frequency = if cpus.is_empty() {
+            None
         } else {
-            self.usages.iter().copied().sum::<f32>() / self.usages.len() as f32
+            let total_mhz: u64 = cpus.iter().map(|cpu| cpu.frequency()).sum();
+            Some(Frequency::from_mhz(total_mhz / cpus.len() as u64)).filter(|f| f.as_hz() > 0)
         };
 
-        self.memory_total = self.system.total_memory();
-        self.memory_used = self.system.used_memory();
+        // sysinfo 0.30 reports memory and swap in bytes.
+        self.memory_total = Bytes(self.system.total_memory());
+        self.memory_used = Bytes(self.system.used_memory());
+        if let Some(container) = &self.container {
+            if let (Some(max), Some(used)) = (container.memory_max(), container.memory_used()) {
+                self.memory_total = max.min(self.memory_total);
+                self.memory_used = used;
+            }
+        }
+        self.swap_total = Bytes(self.system.total_swap());
+        self.swap_used = Bytes(self.system.used_swap());
     }
 
-    /// Average CPU utilization across all threads.
+    /// Average CPU utilization across the tracked threads, or of the cgroup's
+    /// CPU quota in container mode.
     pub fn avg(&self) -> f32 {
         self.avg
     }
 
-    /// Per-thread CPU utilization percentages.
+    /// Per-thread CPU utilization percentages, in the order of [`Self::ids`].
     pub fn usages(&self) -> &[f32] {
         &self.usages
     }
 
-    /// Number of CPU threads being tracked.
-    pub fn thread_count(&self) -> usize {
-        self.usages.len()
+    /// Kernel CPU id of each entry in [`Self::usages`].
+    pub fn ids(&self) -> &[usize] {
+        &self.ids
+    }
+
+    /// Utilization of the CPU with kernel id `id`, `None` when it is offline.
+    pub fn usage(&self, id: usize) -> Option<f32> {
+        let index = self.ids.iter().position(|&cpu| cpu == id)?;
+        self.usages.get(index).copied()
+    }
+
+    /// Recent utilization samples for the CPU with kernel id `id`, oldest first.
+    pub fn history(&self, id: usize) -> Option<&VecDeque<f32>> {
+        let index = self.ids.iter().position(|&cpu| cpu == id)?;
+        self.history.get(index)
+    }
+
+    /// CPU ids shown in the per-thread views: the cgroup's cpuset in
+    /// container mode, otherwise every online thread.
+    pub fn cpus(&self) -> &[usize] {
+        &self.cpus
     }
 
-    /// Memory usage in KiB (used, total).
-    pub fn memory_usage(&self) -> (u64, u64) {
+    /// Limits of the enclosing cgroup when running in container mode.
+    pub fn container(&self) -> Option<&Container> {
+        self.container.as_ref()
+    }
+
+    /// Socket/NUMA/core layout of the tracked threads.
+    pub fn topology(&self) -> &CpuTopology {
+        &self.topology
+    }
+
+    /// Average clock frequency across all threads, when reported.
+    pub fn frequency(&self) -> Option<Frequency> {
+        self.frequency
+    }
+
+    /// Memory usage (used, total).
+    pub fn memory_usage(&self) -> (Bytes, Bytes) {
         (self.memory_used, self.memory_total)
     }
 
     /// Fraction of memory used in the range [0, 1].
     pub fn memory_ratio(&self) -> f64 {
-        if self.memory_total == 0 {
-            0.0
-        } else {
-            self.memory_used as f64 / self.memory_total as f64
-        }
+        self.memory_used.ratio_of(self.memory_total)
+    }
+
+    /// Swap usage (used, total).
+    pub fn swap_usage(&self) -> (Bytes, Bytes) {
+        (self.swap_used, self.swap_total)
+    }
+
+    /// Fraction of swap used in the range [0, 1].
+    pub fn swap_ratio(&self) -> f64 {
+        self.swap_used.ratio_of(self.swap_total)
+    }
+}
+
+/// Kernel CPU id from a sysinfo name such as `cpu12`.
+fn cpu_id(name: &str) -> Option<usize> {
+    name.strip_prefix("cpu")?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cpu_id_parses_sysinfo_names() {
+        assert_eq!(cpu_id("cpu12"), Some(12));
+        assert_eq!(cpu_id("cpu"), None);
+        assert_eq!(cpu_id("Apple M1"), None);
     }
 }
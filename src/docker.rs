@@ -0,0 +1,499 @@
+//! Running Docker/Podman containers from the engine API, with usage read
+//! from each container's cgroup.
+
+use std::{
+    collections::HashMap,
+    env, fs, io,
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver},
+    thread,
+    time::{Duration, Instant},
+};
+
+use serde_json::Value;
+
+use crate::{
+    cgroup::{self, tree},
+    gpu::GpuStats,
+    http, process,
+    units::{ByteRate, Bytes},
+};
+
+/// One running container.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContainerInfo {
+    pub id: String,
+    pub name: String,
+    pub image: String,
+    /// Main process on the host, from the inspect data.
+    pub pid: Option<u32>,
+    /// CPU usage as a percentage of one CPU, from `cpu.stat` deltas.
+    pub cpu_percent: Option<f32>,
+    pub memory: Option<Bytes>,
+    pub memory_max: Option<Bytes>,
+    /// Traffic of the container's network namespace.
+    pub net_rate: Option<ByteRate>,
+    /// `NVIDIA_VISIBLE_DEVICES` or the `--gpus` device request, see [`gpu_indices`].
+    pub gpu_spec: Option<String>,
+}
+
+/// Fields listed by `GET /containers/json`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Summary {
+    pub id: String,
+    pub name: String,
+    pub image: String,
+}
+
+/// Fields used from `GET /containers/<id>/json`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Inspect {
+    pub pid: Option<u32>,
+    pub gpu_spec: Option<String>,
+}
+
+/// Time between engine polls; usage is still sampled on every refresh.
+const ENGINE_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Containers from one engine poll, with inspect data where it was available.
+type EngineList = Vec<(Summary, Option<Inspect>)>;
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Counters {
+    cpu_usec: Option<u64>,
+    net: Option<Bytes>,
+}
+
+/// Polls the engine socket from a background thread while the containers
+/// screen is open.
+pub struct DockerMonitor {
+    socket: Option<PathBuf>,
+    enabled: bool,
+    engine: Option<Receiver<Result<EngineList, String>>>,
+    listed: EngineList,
+    containers: Vec<ContainerInfo>,
+    previous: HashMap<String, Counters>,
+    last_refresh: Option<Instant>,
+    error: Option<String>,
+}
+
+impl DockerMonitor {
+    /// Construct a new monitor and perform an initial refresh.
+    pub fn new() -> Self {
+        let mut monitor = Self::with_socket(find_socket());
+        monitor.refresh();
+        monitor
+    }
+
+    fn with_socket(socket: Option<PathBuf>) -> Self {
+        Self {
+            socket,
+            enabled: false,
+            engine: None,
+            listed: Vec::new(),
+            containers: Vec::new(),
+            previous: HashMap::new(),
+            last_refresh: None,
+            error: None,
+        }
+    }
+
+    /// Start or stop polling the engine.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        if self.enabled != enabled {
+            self.enabled = enabled;
+            // Dropping the receiver ends the previous poller after its current poll.
+            self.engine = self.socket.clone().filter(|_| enabled).map(spawn_poller);
+            self.listed.clear();
+            self.containers.clear();
+            self.previous.clear();
+            self.last_refresh = None;
+        }
+    }
+
+    /// Block until the poller has answered once, so a one-shot caller sees
+    /// the containers on its first refresh.
+    pub fn wait_for_poll(&mut self) {
+        if let Some(result) = self.engine.as_ref().and_then(|engine| engine.recv().ok()) {
+            self.apply(result);
+        }
+    }
+
+    fn apply(&mut self, result: Result<EngineList, String>) {
+        match result {
+            Ok(listed) => {
+                self.error = None;
+                self.listed = listed;
+            }
+            Err(err) => {
+                self.error = Some(err);
+                self.listed.clear();
+            }
+        }
+    }
+
+    /// Take the latest engine poll and compute container usage since the previous refresh.
+    pub fn refresh(&mut self) {
+        let Some(engine) = &self.engine else {
+            return;
+        };
+        if let Some(result) = engine.try_iter().last() {
+            self.apply(result);
+        }
+
+        let now = Instant::now();
+        let elapsed = self
+            .last_refresh
+            .map(|at| now.duration_since(at).as_secs_f64())
+            .unwrap_or(0.0);
+        let mut previous = HashMap::with_capacity(self.listed.len());
+        self.containers = self
+            .listed
+            .iter()
+            .map(|(summary, inspect)| {
+                let inspect = inspect.clone().unwrap_or_default();
+                let dir = inspect
+                    .pid
+                    .and_then(|pid| cgroup::process_cgroup_dir(&pid.to_string()));
+                let read = |file: &str| {
+                    dir.as_ref()
+                        .and_then(|dir| fs::read_to_string(dir.join(file)).ok())
+                };
+                let counters = Counters {
+                    cpu_usec: read("cpu.stat").and_then(|text| tree::parse_cpu_usage(&text)),
+                    net: inspect.pid.and_then(|pid| {
+                        fs::read_to_string(format!("/proc/{pid}/net/dev"))
+                            .ok()
+                            .map(|text| process::parse_net_dev_total(&text))
+                    }),
+                };
+                let before = self.previous.get(&summary.id).copied().unwrap_or_default();
+                let cpu_percent = match (before.cpu_usec, counters.cpu_usec) {
+                    (Some(before), Some(after)) if elapsed > 0.0 => {
+                        Some((after.saturating_sub(before) as f64 / elapsed / 1e4) as f32)
+                    }
+                    _ => None,
+                };
+                let net_rate = match (before.net, counters.net) {
+                    (Some(before), Some(after)) => ByteRate::between(before, after, elapsed),
+                    _ => None,
+                };
+                previous.insert(summary.id.clone(), counters);
+
+                ContainerInfo {
+                    pid: inspect.pid,
+                    cpu_percent,
+                    memory: read("memory.current")
+                        .and_then(|text| tree::parse_limit(&text))
+                        .map(Bytes),
+                    memory_max: read("memory.max")
+                        .and_then(|text| tree::parse_limit(&text))
+                        .map(Bytes),
+                    net_rate,
+                    gpu_spec: inspect.gpu_spec,
+                    id: summary.id.clone(),
+                    name: summary.name.clone(),
+                    image: summary.image.clone(),
+                }
+            })
+            .collect();
+        self.previous = previous;
+        self.last_refresh = Some(now);
+    }
+
+    /// Engine socket in use, if one was found.
+    pub fn socket(&self) -> Option<&Path> {
+        self.socket.as_deref()
+    }
+
+    /// Why the last poll failed, if it did.
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// Running containers from the latest poll, in engine order.
+    pub fn containers(&self) -> &[ContainerInfo] {
+        &self.containers
+    }
+}
+
+/// The engine socket from `DOCKER_HOST`, or the first Docker/Podman default that exists.
+pub fn find_socket() -> Option<PathBuf> {
+    if let Ok(host) = env::var("DOCKER_HOST") {
+        return host.strip_prefix("unix://").map(PathBuf::from);
+    }
+    let mut candidates = vec![
+        PathBuf::from("/var/run/docker.sock"),
+        PathBuf::from("/run/podman/podman.sock"),
+    ];
+    if let Ok(runtime) = env::var("XDG_RUNTIME_DIR") {
+        candidates.push(Path::new(&runtime).join("podman/podman.sock"));
+        candidates.push(Path::new(&runtime).join("docker.sock"));
+    }
+    candidates.into_iter().find(|path| path.exists())
+}
+
+/// Poll the engine every [`ENGINE_INTERVAL`] until the receiver is dropped.
+fn spawn_poller(socket: PathBuf) -> Receiver<Result<EngineList, String>> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let mut inspected = HashMap::new();
+        loop {
+            let result = poll(&socket, &mut inspected).map_err(|err| err.to_string());
+            if sender.send(result).is_err() {
+                break;
+            }
+            thread::sleep(ENGINE_INTERVAL);
+        }
+    });
+    receiver
+}
+
+/// List running containers, inspecting each one once. Failed inspects are
+/// remembered too, so a container the engine cannot describe is not re-asked
+/// every poll.
+fn poll(socket: &Path, inspected: &mut HashMap<String, Option<Inspect>>) -> io::Result<EngineList> {
+    let summaries = list(socket)?;
+    inspected.retain(|id, _| summaries.iter().any(|summary| &summary.id == id));
+    Ok(summaries
+        .into_iter()
+        .map(|summary| {
+            let details = inspected
+                .entry(summary.id.clone())
+                .or_insert_with(|| inspect(socket, &summary.id).ok())
+                .clone();
+            (summary, details)
+        })
+        .collect())
+}
+
+/// Running containers known to the engine.
+pub fn list(socket: &Path) -> io::Result<Vec<Summary>> {
+    http::get_unix(socket, "/containers/json").map(|body| parse_list(&body))
+}
+
+/// Host pid and GPU request of one container.
+pub fn inspect(socket: &Path, id: &str) -> io::Result<Inspect> {
+    http::get_unix(socket, &format!("/containers/{id}/json")).map(|body| parse_inspect(&body))
+}
+
+/// Parse the `/containers/json` array, skipping malformed entries.
+pub fn parse_list(body: &str) -> Vec<Summary> {
+    let Ok(Value::Array(entries)) = serde_json::from_str(body) else {
+        return Vec::new();
+    };
+    entries
+        .iter()
+        .filter_map(|entry| {
+            let id = entry["Id"].as_str()?.to_string();
+            let name = entry["Names"][0]
+                .as_str()
+                .map(|name| name.trim_start_matches('/').to_string())
+                .unwrap_or_else(|| id.chars().take(12).collect());
+            Some(Summary {
+                id,
+                name,
+                image: entry["Image"].as_str().unwrap_or_default().to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Parse inspect output; `--gpus` device requests take precedence over the
+/// `NVIDIA_VISIBLE_DEVICES` environment variable.
+pub fn parse_inspect(body: &str) -> Inspect {
+    let Ok(value) = serde_json::from_str::<Value>(body) else {
+        return Inspect::default();
+    };
+    let pid = value["State"]["Pid"]
+        .as_u64()
+        .filter(|&pid| pid > 0)
+        .and_then(|pid| u32::try_from(pid).ok());
+
+    let requested = value["HostConfig"]["DeviceRequests"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .find(|request| {
+            request["Capabilities"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .flat_map(|set| set.as_array().into_iter().flatten())
+                .any(|capability| capability == "gpu")
+        })
+        .and_then(|request| match request["DeviceIDs"].as_array() {
+            Some(ids) if !ids.is_empty() => Some(
+                ids.iter()
+                    .filter_map(Value::as_str)
+                    .collect::<Vec<_>>()
+                    .join(","),
+            ),
+            _ => (request["Count"].as_i64() == Some(-1)).then(|| "all".to_string()),
+        });
+    let env = value["Config"]["Env"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+        .find_map(|var| var.strip_prefix("NVIDIA_VISIBLE_DEVICES="))
+        .map(str::to_string);
+
+    Inspect {
+        pid,
+        gpu_spec: requested.or(env),
+    }
+}
+
+/// Map an `NVIDIA_VISIBLE_DEVICES` value (indices, UUIDs, `all`, `none`) to
+/// [`GpuStats::index`] values of the GPUs present.
+pub fn gpu_indices(spec: &str, gpus: &[GpuStats]) -> Vec<u32> {
+    match spec.trim() {
+        "all" => gpus.iter().map(|gpu| gpu.index).collect(),
+        "" | "none" | "void" => Vec::new(),
+        list => list
+            .split(',')
+            .map(str::trim)
+            .filter_map(|device| match device.parse::<u32>() {
+                Ok(index) => gpus.iter().find(|gpu| gpu.index == index),
+                Err(_) => gpus.iter().find(|gpu| gpu.uuid.as_deref() == Some(device)),
+            })
+            .map(|gpu| gpu.index)
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDir;
+    use std::{
+        io::{BufRead, BufReader, Write},
+        os::unix::net::UnixListener,
+        thread,
+    };
+
+    fn gpu(index: u32, uuid: &str) -> GpuStats {
+        GpuStats {
+            index,
+            name: format!("GPU {index}"),
+            uuid: Some(uuid.to_string()),
+            utilization: 0.0,
+            memory_used: Bytes(0),
+            memory_total: Bytes(0),
+            temperature: None,
+            processes: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn gpu_indices_resolves_indices_uuids_and_keywords() {
+        let gpus = [gpu(0, "GPU-aaa"), gpu(1, "GPU-bbb")];
+        assert_eq!(gpu_indices("all", &gpus), vec![0, 1]);
+        assert_eq!(gpu_indices("none", &gpus), Vec::<u32>::new());
+        assert_eq!(gpu_indices("1", &gpus), vec![1]);
+        assert_eq!(gpu_indices("GPU-bbb,0,7", &gpus), vec![1, 0]);
+    }
+
+    #[test]
+    fn parse_inspect_prefers_device_requests() {
+        let env_only =
+            r#"{"State":{"Pid":4242},"Config":{"Env":["PATH=/bin","NVIDIA_VISIBLE_DEVICES=0,1"]}}"#;
+        assert_eq!(
+            parse_inspect(env_only),
+            Inspect {
+                pid: Some(4242),
+                gpu_spec: Some("0,1".to_string())
+            }
+        );
+        let requested = r#"{"State":{"Pid":0},"Config":{"Env":[]},
+            "HostConfig":{"DeviceRequests":[{"Count":-1,"DeviceIDs":null,"Capabilities":[["gpu"]]}]}}"#;
+        assert_eq!(
+            parse_inspect(requested),
+            Inspect {
+                pid: None,
+                gpu_spec: Some("all".to_string())
+            }
+        );
+    }
+
+    /// Serve canned engine responses on a Unix socket and return the request
+    /// lines; the inspect reply is chunked like the real daemon's, and `def456`
+    /// cannot be inspected.
+    fn fake_engine(socket: &Path, connections: usize) -> thread::JoinHandle<Vec<String>> {
+        let listener = UnixListener::bind(socket).unwrap();
+        thread::spawn(move || {
+            let mut requests = Vec::new();
+            for stream in listener.incoming().take(connections) {
+                let mut stream = stream.unwrap();
+                let mut request = String::new();
+                BufReader::new(&stream).read_line(&mut request).unwrap();
+                let response = if request.starts_with("GET /containers/json ") {
+                    let body = r#"[{"Id":"abc123","Names":["/web"],"Image":"nginx:1.27"},
+                        {"Id":"def456","Names":["/db"],"Image":"postgres:17"}]"#;
+                    format!("HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n\r\n{body}")
+                } else if request.starts_with("GET /containers/abc123/json ") {
+                    let body = r#"{"State":{"Pid":999999999},"Config":{"Env":["NVIDIA_VISIBLE_DEVICES=GPU-bbb"]}}"#;
+                    format!(
+                        "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n{:x}\r\n{body}\r\n0\r\n\r\n",
+                        body.len()
+                    )
+                } else {
+                    "HTTP/1.1 404 Not Found\r\n\r\n".to_string()
+                };
+                stream.write_all(response.as_bytes()).unwrap();
+                requests.push(request.trim_end().to_string());
+            }
+            requests
+        })
+    }
+
+    #[test]
+    fn monitor_reads_containers_from_fake_engine() {
+        let dir = TempDir::new("engine");
+        let socket = dir.path().join("docker.sock");
+        let server = fake_engine(&socket, 3);
+
+        let mut monitor = DockerMonitor::with_socket(Some(socket.clone()));
+        monitor.set_enabled(true);
+        monitor.wait_for_poll();
+        monitor.refresh();
+        server.join().unwrap();
+
+        assert_eq!(monitor.error(), None);
+        let [web, db] = monitor.containers() else {
+            panic!("expected two containers");
+        };
+        assert_eq!(web.name, "web");
+        assert_eq!(web.image, "nginx:1.27");
+        assert_eq!(web.pid, Some(999_999_999));
+        assert_eq!(web.gpu_spec.as_deref(), Some("GPU-bbb"));
+        assert_eq!(web.memory, None);
+        assert_eq!(db.name, "db");
+        assert_eq!(db.pid, None);
+    }
+
+    #[test]
+    fn poll_inspects_each_container_once() {
+        let dir = TempDir::new("poll");
+        let socket = dir.path().join("docker.sock");
+        let server = fake_engine(&socket, 4);
+
+        let mut inspected = HashMap::new();
+        let first = poll(&socket, &mut inspected).unwrap();
+        let second = poll(&socket, &mut inspected).unwrap();
+        let requests = server.join().unwrap();
+
+        assert_eq!(first, second);
+        assert_eq!(first[1].1, None);
+        assert_eq!(
+            requests,
+            [
+                "GET /containers/json HTTP/1.1",
+                "GET /containers/abc123/json HTTP/1.1",
+                "GET /containers/def456/json HTTP/1.1",
+                "GET /containers/json HTTP/1.1",
+            ]
+        );
+    }
+}
@@ -1,21 +1,32 @@
 //! GPU monitoring via NVIDIA's NVML (when available).
 
-use nvml_wrapper::{enum_wrappers::device::TemperatureSensor, error::NvmlError, Nvml};
+use std::collections::HashMap;
+
+use nvml_wrapper::{
+    enum_wrappers::device::TemperatureSensor, enums::device::UsedGpuMemory, error::NvmlError, Nvml,
+};
+
+use crate::units::{Bytes, Temperature};
 
 #[derive(Debug, Clone)]
 /// Snapshot of a single GPU's metrics.
 pub struct GpuStats {
     pub index: u32,
     pub name: String,
+    /// Device UUID such as `GPU-5d7c…`, as used by `NVIDIA_VISIBLE_DEVICES`.
+    pub uuid: Option<String>,
     pub utilization: f32,
-    pub memory_used: u64,
-    pub memory_total: u64,
-    pub temperature: Option<u32>,
+    pub memory_used: Bytes,
+    pub memory_total: Bytes,
+    pub temperature: Option<Temperature>,
+    /// Processes using this device and their GPU memory, by pid.
+    pub processes: Vec<(u32, Bytes)>,
 }
 
 pub struct GpuMonitor {
     nvml: Option<Nvml>,
     stats: Vec<GpuStats>,
+    process_memory: HashMap<u32, Bytes>,
 }
 
 impl GpuMonitor {
@@ -25,6 +36,7 @@ impl GpuMonitor {
         let mut monitor = Self {
             nvml,
             stats: Vec::new(),
+            process_memory: HashMap::new(),
         };
         monitor.refresh();
         monitor
@@ -37,6 +49,7 @@ impl GpuMonitor {
                 Ok(nvml) => self.nvml = Some(nvml),
                 Err(_) => {
                     self.stats.clear();
+                    self.process_memory.clear();
                     return;
                 }
             }
@@ -44,6 +57,7 @@ impl GpuMonitor {
 
         let Some(nvml) = self.nvml.as_ref() else {
             self.stats.clear();
+            self.process_memory.clear();
             return;
         };
 
@@ -51,10 +65,12 @@ impl GpuMonitor {
             Ok(count) => count,
             Err(_) => {
                 self.stats.clear();
+                self.process_memory.clear();
                 return;
             }
         };
 
+        let mut process_memory: HashMap<u32, Bytes> = HashMap::new();
         let mut stats = Vec::with_capacity(device_count as usize);
         for i in 0..device_count {
             match nvml.device_by_index(i) {
@@ -66,17 +82,39 @@ impl GpuMonitor {
                         .unwrap_or(0.0);
                     let (memory_used, memory_total) = device
                         .memory_info()
-                        .map(|m| (m.used, m.total))
-                        .unwrap_or((0, 0));
-                    let temperature = device.temperature(TemperatureSensor::Gpu).ok();
+                        .map(|m| (Bytes(m.used), Bytes(m.total)))
+                        .unwrap_or_default();
+                    let temperature = device
+                        .temperature(TemperatureSensor::Gpu)
+                        .ok()
+                        .map(|t| Temperature(t as f32));
+
+                    // A process may be listed as both compute and graphics
+                    // on the same device; count it once per device.
+                    let mut device_memory: HashMap<u32, Bytes> = HashMap::new();
+                    let compute = device.running_compute_processes().unwrap_or_default();
+                    let graphics = device.running_graphics_processes().unwrap_or_default();
+                    for process in compute.into_iter().chain(graphics) {
+                        if let UsedGpuMemory::Used(bytes) = process.used_gpu_memory {
+                            let entry = device_memory.entry(process.pid).or_default();
+                            *entry = (*entry).max(Bytes(bytes));
+                        }
+                    }
+                    for (&pid, &bytes) in &device_memory {
+                        *process_memory.entry(pid).or_default() += bytes;
+                    }
+                    let mut processes: Vec<(u32, Bytes)> = device_memory.into_iter().collect();
+                    processes.sort_unstable();
 
                     stats.push(GpuStats {
                         index: i,
                         name,
+                        uuid: device.uuid().ok(),
                         utilization,
                         memory_used,
                         memory_total,
                         temperature,
+                        processes,
                     });
                 }
                 Err(NvmlError::NotSupported) => {
@@ -88,6 +126,7 @@ impl GpuMonitor {
         }
 
         self.stats = stats;
+        self.process_memory = process_memory;
     }
 
     /// Latest GPU statistics snapshots.
@@ -95,6 +134,11 @@ impl GpuMonitor {
         &self.stats
     }
 
+    /// GPU memory used by `pid` summed over all devices, if it uses any.
+    pub fn process_memory(&self, pid: u32) -> Option<Bytes> {
+        self.process_memory.get(&pid).copied()
+    }
+
     /// Whether NVML is currently available.
     pub fn nvml_available(&self) -> bool {
         self.nvml.is_some()
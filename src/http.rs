@@ -0,0 +1,134 @@
+//! Minimal HTTP/1.1 client over Unix and TCP sockets, enough for the
+//! container engine and kubelet APIs and InfluxDB writes.
+
+use std::{
+    io::{self, Read, Write},
+    net::{TcpStream, ToSocketAddrs},
+    os::unix::net::UnixStream,
+    path::Path,
+    time::Duration,
+};
+
+/// How long a request may block the refresh loop.
+const TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Fetch `path` from the server listening on the Unix socket `socket`.
+pub fn get_unix(socket: &Path, path: &str) -> io::Result<String> {
+    let stream = UnixStream::connect(socket)?;
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+    request(stream, "GET", path, "")
+}
+
+/// Fetch `path` from the server at `addr` (`host:port`).
+pub fn get_tcp(addr: &str, path: &str) -> io::Result<String> {
+    request(connect_tcp(addr)?, "GET", path, "")
+}
+
+/// POST a plain-text `body` to `path` on the server at `addr` (`host:port`).
+pub fn post_tcp(addr: &str, path: &str, body: &str) -> io::Result<String> {
+    request(connect_tcp(addr)?, "POST", path, body)
+}
+
+fn connect_tcp(addr: &str) -> io::Result<TcpStream> {
+    let addr = addr
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "address did not resolve"))?;
+    let stream = TcpStream::connect_timeout(&addr, TIMEOUT)?;
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+    Ok(stream)
+}
+
+fn request<S: Read + Write>(
+    mut stream: S,
+    method: &str,
+    path: &str,
+    body: &str,
+) -> io::Result<String> {
+    // One write, so servers that answer after the request line still get it all.
+    let mut request =
+        format!("{method} {path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n");
+    if !body.is_empty() {
+        request.push_str(&format!(
+            "Content-Type: text/plain; charset=utf-8\r\nContent-Length: {}\r\n",
+            body.len()
+        ));
+    }
+    request.push_str("\r\n");
+    request.push_str(body);
+    stream.write_all(request.as_bytes())?;
+    let mut raw = Vec::new();
+    stream.read_to_end(&mut raw)?;
+    parse_response(&raw)
+}
+
+/// Split a raw response into status, headers and body, decoding chunked bodies.
+pub fn parse_response(raw: &[u8]) -> io::Result<String> {
+    let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, message.to_string());
+    let split = raw
+        .windows(4)
+        .position(|window| window == b"\r\n\r\n")
+        .ok_or_else(|| invalid("truncated HTTP response"))?;
+    let head = String::from_utf8_lossy(&raw[..split]);
+    let body = &raw[split + 4..];
+
+    let mut lines = head.lines();
+    let status: u16 = lines
+        .next()
+        .and_then(|line| line.split_whitespace().nth(1))
+        .and_then(|code| code.parse().ok())
+        .ok_or_else(|| invalid("malformed HTTP status line"))?;
+    let chunked = lines.any(|line| {
+        line.split_once(':').is_some_and(|(name, value)| {
+            name.trim().eq_ignore_ascii_case("transfer-encoding")
+                && value.trim().eq_ignore_ascii_case("chunked")
+        })
+    });
+
+    let body = if chunked {
+        decode_chunked(body).ok_or_else(|| invalid("malformed chunked body"))?
+    } else {
+        body.to_vec()
+    };
+    if !(200..300).contains(&status) {
+        return Err(io::Error::other(format!("HTTP {status}")));
+    }
+    String::from_utf8(body).map_err(|_| invalid("response body is not UTF-8"))
+}
+
+fn decode_chunked(mut body: &[u8]) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(body.len());
+    loop {
+        let line_end = body.windows(2).position(|window| window == b"\r\n")?;
+        let size_field = std::str::from_utf8(&body[..line_end]).ok()?;
+        // Chunk extensions after `;` are allowed and ignored.
+        let size = usize::from_str_radix(size_field.split(';').next()?.trim(), 16).ok()?;
+        body = &body[line_end + 2..];
+        if size == 0 {
+            return Some(out);
+        }
+        out.extend_from_slice(body.get(..size)?);
+        body = body.get(size + 2..)?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_response_handles_plain_chunked_and_errors() {
+        let plain = b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\n[]";
+        assert_eq!(parse_response(plain).unwrap(), "[]");
+
+        let chunked =
+            b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n3\r\n{\"a\r\n4;x=y\r\n\":1}\r\n0\r\n\r\n";
+        assert_eq!(parse_response(chunked).unwrap(), "{\"a\":1}");
+
+        let missing = b"HTTP/1.1 404 Not Found\r\n\r\n{}";
+        assert_eq!(parse_response(missing).unwrap_err().to_string(), "HTTP 404");
+        assert!(parse_response(b"HTTP/1.1 200 OK\r\n").is_err());
+    }
+}
@@ -0,0 +1,315 @@
+//! Kubernetes pod attribution from cgroup paths, with names resolved through
+//! the kubelet's read-only endpoint.
+
+use std::{
+    collections::HashMap,
+    env, io,
+    sync::mpsc::{self, Receiver},
+    thread,
+    time::Duration,
+};
+
+use serde_json::Value;
+
+use crate::{http, process::ProcessInfo};
+
+/// Kubelet read-only endpoint used unless `RUSTOP_KUBELET` overrides it.
+const DEFAULT_KUBELET: &str = "127.0.0.1:10255";
+/// How often the pod list is re-fetched while pods are running.
+const POD_LIST_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Pod and container identifiers found in a cgroup path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PodRef {
+    /// Pod UID in its canonical dashed form.
+    pub pod_uid: String,
+    /// Runtime container ID, absent for the pod's own slice.
+    pub container_id: Option<String>,
+}
+
+/// Names of one pod from the kubelet.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PodMeta {
+    pub namespace: String,
+    pub name: String,
+    /// Container names by runtime container ID.
+    pub containers: HashMap<String, String>,
+}
+
+/// Pod names by UID, from one kubelet poll.
+type PodList = HashMap<String, PodMeta>;
+
+/// Tracks whether pods run on this node and resolves their names from a
+/// background thread, so a slow kubelet never stalls a refresh.
+pub struct KubeMonitor {
+    endpoint: Option<String>,
+    active: bool,
+    pods: PodList,
+    poller: Option<Receiver<io::Result<PodList>>>,
+    /// Whether the poller has answered at least once.
+    answered: bool,
+}
+
+impl KubeMonitor {
+    /// Create a monitor using `RUSTOP_KUBELET` (`host:port`, or `off` to skip
+    /// name resolution) or the default read-only port.
+    pub fn new() -> Self {
+        let endpoint = match env::var("RUSTOP_KUBELET") {
+            Ok(value) if value == "off" => None,
+            Ok(value) => Some(value),
+            Err(_) => Some(DEFAULT_KUBELET.to_string()),
+        };
+        Self::with_endpoint(endpoint)
+    }
+
+    fn with_endpoint(endpoint: Option<String>) -> Self {
+        Self {
+            endpoint,
+            active: false,
+            pods: HashMap::new(),
+            poller: None,
+            answered: false,
+        }
+    }
+
+    /// Note whether any process runs in a pod, polling the kubelet only while
+    /// one does, and take the latest pod names.
+    pub fn refresh(&mut self, processes: &[ProcessInfo]) {
+        self.active = processes
+            .iter()
+            .any(|p| p.cgroup.as_deref().and_then(parse_pod_cgroup).is_some());
+        if !self.active {
+            // Dropping the receiver ends the poller after its current fetch.
+            self.poller = None;
+            return;
+        }
+        if self.poller.is_none() {
+            self.poller = self.endpoint.clone().map(spawn_poller);
+        }
+        let latest = self
+            .poller
+            .as_ref()
+            .and_then(|poller| poller.try_iter().last());
+        if let Some(result) = latest {
+            self.apply(result);
+        }
+    }
+
+    /// Block until the kubelet has answered once, so a one-shot caller sees
+    /// pod names.
+    pub fn wait_for_poll(&mut self) {
+        if self.answered {
+            return;
+        }
+        if let Some(result) = self.poller.as_ref().and_then(|poller| poller.recv().ok()) {
+            self.apply(result);
+        }
+    }
+
+    fn apply(&mut self, result: io::Result<PodList>) {
+        self.answered = true;
+        // Keep the previous names when the kubelet is briefly unreachable.
+        if let Ok(pods) = result {
+            self.pods = pods;
+        }
+    }
+
+    /// Whether any process on this node runs inside a pod.
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// `namespace/pod/container` for a cgroup path, falling back to the pod
+    /// UID when the kubelet did not provide names.
+    pub fn pod_label(&self, cgroup: Option<&str>) -> Option<String> {
+        let pod = parse_pod_cgroup(cgroup?)?;
+        let Some(meta) = self.pods.get(&pod.pod_uid) else {
+            return Some(format!("pod {}", &pod.pod_uid[..pod.pod_uid.len().min(8)]));
+        };
+        let container = pod
+            .container_id
+            .as_ref()
+            .and_then(|id| meta.containers.get(id));
+        Some(match container {
+            Some(container) => format!("{}/{}/{container}", meta.namespace, meta.name),
+            None => format!("{}/{}", meta.namespace, meta.name),
+        })
+    }
+}
+
+/// Fetch the pod list every [`POD_LIST_INTERVAL`] until the receiver is dropped.
+fn spawn_poller(endpoint: String) -> Receiver<io::Result<PodList>> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || loop {
+        let result = http::get_tcp(&endpoint, "/pods").map(|body| parse_pod_list(&body));
+        if sender.send(result).is_err() {
+            break;
+        }
+        thread::sleep(POD_LIST_INTERVAL);
+    });
+    receiver
+}
+
+/// Find the pod UID and container ID in a kubelet-managed cgroup path, for
+/// both the systemd (`kubepods-burstable-pod<uid>.slice/cri-containerd-<id>.scope`)
+/// and cgroupfs (`kubepods/burstable/pod<uid>/<id>`) drivers.
+pub fn parse_pod_cgroup(path: &str) -> Option<PodRef> {
+    let components: Vec<&str> = path.split('/').filter(|c| !c.is_empty()).collect();
+    if !components.iter().any(|c| c.starts_with("kubepods")) {
+        return None;
+    }
+    let (index, pod_uid) = components
+        .iter()
+        .enumerate()
+        .find_map(|(index, component)| Some((index, pod_uid(component)?)))?;
+    Some(PodRef {
+        pod_uid,
+        container_id: components
+            .get(index + 1)
+            .and_then(|component| container_id(component)),
+    })
+}
+
+fn pod_uid(component: &str) -> Option<String> {
+    let name = component.strip_suffix(".slice").unwrap_or(component);
+    let start = name.rfind("pod")?;
+    let (prefix, uid) = (&name[..start], &name[start + 3..]);
+    let valid = (prefix.is_empty() || prefix.ends_with('-'))
+        && uid.len() >= 32
+        && uid
+            .chars()
+            .all(|c| c.is_ascii_hexdigit() || c == '-' || c == '_');
+    // The systemd driver escapes the UID's dashes as underscores.
+    valid.then(|| uid.replace('_', "-"))
+}
+
+fn container_id(component: &str) -> Option<String> {
+    let name = component.strip_suffix(".scope").unwrap_or(component);
+    let id = name.rsplit('-').next()?;
+    (id.len() >= 12 && id.chars().all(|c| c.is_ascii_hexdigit())).then(|| id.to_string())
+}
+
+/// Parse the kubelet's `/pods` response into pod names keyed by UID.
+pub fn parse_pod_list(body: &str) -> PodList {
+    let Ok(value) = serde_json::from_str::<Value>(body) else {
+        return HashMap::new();
+    };
+    value["items"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|pod| {
+            let metadata = &pod["metadata"];
+            let uid = metadata["uid"].as_str()?.to_string();
+            let statuses = ["containerStatuses", "initContainerStatuses"];
+            let containers = statuses
+                .iter()
+                .flat_map(|key| pod["status"][key].as_array().into_iter().flatten())
+                .filter_map(|status| {
+                    let id = status["containerID"].as_str()?;
+                    // `containerd://<id>`, `cri-o://<id>`, `docker://<id>`.
+                    let id = id.split_once("://").map_or(id, |(_, id)| id);
+                    Some((id.to_string(), status["name"].as_str()?.to_string()))
+                })
+                .collect();
+            Some((
+                uid,
+                PodMeta {
+                    namespace: metadata["namespace"]
+                        .as_str()
+                        .unwrap_or_default()
+                        .to_string(),
+                    name: metadata["name"].as_str().unwrap_or_default().to_string(),
+                    containers,
+                },
+            ))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        io::{BufRead, BufReader, Write},
+        net::TcpListener,
+        thread,
+    };
+
+    const UID: &str = "0f1e2d3c-4b5a-6978-8695-a4b3c2d1e0f9";
+    const CID: &str = "3b9a1c0de5f64a7b8c9d0e1f2a3b4c5d6e7f8091a2b3c4d5e6f708192a3b4c5d";
+
+    fn process(pid: u32, cgroup: &str) -> ProcessInfo {
+        ProcessInfo {
+            cgroup: Some(cgroup.to_string()),
+            ..ProcessInfo::test(pid)
+        }
+    }
+
+    #[test]
+    fn parse_pod_cgroup_handles_both_drivers() {
+        let systemd = format!(
+            "/kubepods.slice/kubepods-burstable.slice/kubepods-burstable-pod{}.slice/cri-containerd-{CID}.scope",
+            UID.replace('-', "_")
+        );
+        let expected = PodRef {
+            pod_uid: UID.to_string(),
+            container_id: Some(CID.to_string()),
+        };
+        assert_eq!(parse_pod_cgroup(&systemd), Some(expected.clone()));
+        assert_eq!(
+            parse_pod_cgroup(&format!("/kubepods/besteffort/pod{UID}/{CID}")),
+            Some(expected)
+        );
+        assert_eq!(
+            parse_pod_cgroup(&format!("/kubepods/pod{UID}")),
+            Some(PodRef {
+                pod_uid: UID.to_string(),
+                container_id: None
+            })
+        );
+        assert_eq!(
+            parse_pod_cgroup("/kubepods.slice/kubepods-burstable.slice"),
+            None
+        );
+        assert_eq!(parse_pod_cgroup("/system.slice/docker-abc.scope"), None);
+    }
+
+    #[test]
+    fn resolves_names_from_stub_kubelet() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = String::new();
+            BufReader::new(&stream).read_line(&mut request).unwrap();
+            assert!(request.starts_with("GET /pods "));
+            let body = format!(
+                r#"{{"items":[{{"metadata":{{"name":"trainer-0","namespace":"ml","uid":"{UID}"}},
+                "status":{{"containerStatuses":[{{"name":"worker","containerID":"containerd://{CID}"}}]}}}}]}}"#
+            );
+            write!(stream, "HTTP/1.1 200 OK\r\n\r\n{body}").unwrap();
+        });
+
+        let cgroup = format!("/kubepods/burstable/pod{UID}/{CID}");
+        let mut monitor = KubeMonitor::with_endpoint(Some(addr));
+        monitor.refresh(&[
+            process(10, "/system.slice/sshd.service"),
+            process(11, &cgroup),
+        ]);
+        monitor.wait_for_poll();
+        server.join().unwrap();
+
+        assert!(monitor.is_active());
+        assert_eq!(
+            monitor.pod_label(Some(&cgroup)).as_deref(),
+            Some("ml/trainer-0/worker")
+        );
+        assert_eq!(monitor.pod_label(Some("/system.slice/sshd.service")), None);
+        let unknown = "/kubepods/pod11111111-2222-3333-4444-555555555555";
+        assert_eq!(
+            monitor.pod_label(Some(unknown)).as_deref(),
+            Some("pod 11111111")
+        );
+    }
+}
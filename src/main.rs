@@ -5,15 +5,40 @@
 //! `rustop` is a terminal-based system monitor written in Rust. It shows:
 //! - Per-thread CPU load
 //! - Combined CPU utilization
-//! - Memory usage
+//! - Memory usage, with a breakdown of buffers, page cache, shared memory, slab, dirty/writeback, hugepages and commit
+//! - Swap usage and swap-in/out rates, zram compression and zswap pool usage
+//! - CPU, memory and I/O pressure stall information (PSI) with history
 //! - GPU utilization (NVIDIA NVML)
 //! - GPU memory usage
 //! - GPU temperature
+//! - Load averages, uptime, task counts and context switch/interrupt rates
+//! - Sortable process table with CPU, memory, disk read/write and container network rates, and a per-process detail pane (cmdline, cwd, exe, environment, status, RSS/PSS/USS, open files, threads, limits, cgroup, namespaces)
+//! - Process warnings: zombies (with the parent that has not reaped them), processes stuck in uninterruptible sleep (`D`) for more than 10 seconds (`--d-state-secs` to change), and processes whose RSS or open file descriptor count never shrank over the last two minutes, shown above the main screen with their wait channel (`wchan`)
+//! - cgroup v2 tree browser with per-group CPU usage, memory.current against memory.max, io.stat throughput, pids.current and pressure
+//! - Container-aware mode: when rustop's own cgroup has a `cpu.max` quota, `memory.max` or a narrower `cpuset.cpus.effective`, the CPU gauge is scaled to the quota, RAM is shown against `memory.max` (working set excluding inactive file cache), the per-thread views only list the cpuset's CPUs, and the banner names the cgroup and its limits
+//! - Docker/Podman containers screen listing running containers from the engine's Unix socket (`DOCKER_HOST` or the default Docker/Podman sockets) with image, cgroup CPU and memory, network rate and assigned GPUs (from `--gpus` or `NVIDIA_VISIBLE_DEVICES`)
+//! - Kubernetes pod attribution: pod UID and container ID parsed from kubelet cgroup paths, with names resolved through the kubelet read-only endpoint (`RUSTOP_KUBELET=host:port`, or `off`), shown in a POD column of the process table and as per-pod memory on each GPU
+//! - `rustop --json` (or `rustop snapshot`) samples once over a second, prints every metric (per-thread CPU, memory, swap, pressure, GPUs, processes, warnings, cgroups and containers) as one JSON document and exits without touching the terminal; sizes are bytes, rates bytes per second
+//! - `rustop record --format ndjson|csv --interval 1s --output run.csv` writes one timestamped record per sample (overall and per-thread CPU, memory, swap, load, and each GPU's utilization, VRAM and temperature flattened into columns) until interrupted; `--rotate-size 100M` or `--rotate-every 1h` moves the current file aside as `run.csv.<unix millis>` and starts a new one
+//! - Prometheus exporter: `rustop --prometheus 127.0.0.1:9500` serves `/metrics` alongside the TUI, `rustop serve --prometheus 127.0.0.1:9500` without it; exposes overall and per-CPU utilization, frequency, memory, swap, load and per-GPU utilization, memory, temperature and per-process GPU memory (labelled by `cpu`, `gpu`, `name`, `uuid`, `pid`) in the text exposition format
+//! - Push outputs, alongside the TUI or under `rustop serve`: `--influx` writes InfluxDB line protocol to a file, stdout (`-`, serve only) or an HTTP endpoint such as `http://127.0.0.1:8086/write?db=rustop`, and `--statsd 127.0.0.1:8125` sends gauges like `rustop.cpu.0.usage_percent` over UDP with `--statsd-prefix` and DogStatsD `--statsd-tag key:value`; both push every `--push-interval` (default 10s)
 //!
 //! ## Usage
 //! - Requirements: Rust toolchain; NVIDIA drivers/NVML for GPU stats (CPU/RAM work without GPU support).
-//! - Run: `cargo run --release`
+//! - Run: `cargo run --release` (pass `--si` for kB/MB/GB sizes or `--fahrenheit` for °F; see `--help`).
 //! - Quit: press `F10` or `q`/`Q`.
+//! - `t`: group the per-thread table by socket, NUMA node and core (P/E cores labelled on hybrid CPUs).
+//! - `h`: toggle between per-thread bars and a usage heatmap (threads as rows, recent samples as columns).
+//! - `p`: switch the pressure panel between system-wide PSI and rustop's own cgroup.
+//! - `u`: switch sizes between IEC (KiB, MiB, GiB) and SI (kB, MB, GB) units; `f`: switch temperatures between °C and °F.
+//! - `Tab`: cycle through the overview, process, cgroup and container screens. On the process screen, `Up`/`Down` select a process, `Enter` opens its detail pane, `PgUp`/`PgDn` scroll it and `Esc` closes it. On the cgroup screen, `Up`/`Down` select a group, `Right`/`Enter` expand it and `Left` collapses it or moves to its parent.
+//! - `m`: cycle the process table's memory column between RSS, PSS, USS and swap (PSS/USS/swap are read from `smaps_rollup` every 5 seconds while selected).
+//! - `s`: cycle the process table's sort column (CPU, memory, disk read, disk write, network, PID). Network rates are per network namespace, so they are only shown for processes outside the host namespace.
+//! - `e`: edit the selected process's nice value, I/O priority and CPU affinity (a checkbox grid laid out like the per-thread table). `Up`/`Down` pick a field, `Left`/`Right` change it, `Space` toggles a CPU, `a` allows all CPUs, `Enter` applies to every thread and `Esc` closes. Raising priority or changing another user's process needs root or `CAP_SYS_NICE`; the realtime I/O class needs root or `CAP_SYS_ADMIN`.
+//! - `x`: expand the selected process into its threads (name, state, CPU% and the CPU each last ran on). `c`: highlight the cores the selected process's threads occupy in the per-thread table.
+//! - `o`: core occupancy map. Each CPU in the per-thread table lists the processes whose threads were last scheduled on it, weighted by CPU time since the previous refresh.
+//! - `g`: group the process table by user, executable name, cgroup (innermost systemd slice) or systemd unit (`nginx.service`, with user services under `user@<uid>.service`), showing summed CPU, memory, disk I/O, GPU memory, process and task count per group.
+//! - `l`: show the process event log (starts, exits with runtime and peak CPU/RSS, and short-lived processes caught by the netlink proc connector when running with `CAP_NET_ADMIN`); `[`/`]` scroll it. Newly started processes are shown in green and just-exited ones in red for a few refreshes.
 //! - Docs: <https://jbeougher7924.github.io/rustop>
 //!
 //! ## Docs and README maintenance
@@ -28,11 +53,44 @@
 //! Licensed under the GNU General Public License v3.0 (GPL-3.0).
 
 mod app;
+mod cgroup;
+mod cli;
+mod container;
 mod cpu;
+mod docker;
 mod gpu;
+mod http;
+mod kubernetes;
+mod memory;
+mod monitors;
+mod pressure;
+mod process;
+mod prometheus;
+mod push;
+mod record;
+mod snapshot;
+mod swap;
+mod system;
+#[cfg(test)]
+mod testing;
+mod topology;
 mod ui;
+mod units;
 mod utilities;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    app::run()
+    match cli::parse_args(std::env::args().skip(1)) {
+        Ok(cli::Command::Tui(options)) => app::run(options),
+        Ok(cli::Command::Snapshot) => snapshot::run(),
+        Ok(cli::Command::Record(options)) => record::run(options),
+        Ok(cli::Command::Serve(options)) => app::serve(options),
+        Ok(cli::Command::Help) => {
+            println!("{}", cli::USAGE);
+            Ok(())
+        }
+        Err(message) => {
+            eprintln!("rustop: {message}\n\n{}", cli::USAGE);
+            std::process::exit(2);
+        }
+    }
 }
@@ -0,0 +1,150 @@
+//! Detailed memory breakdown parsed from `/proc/meminfo`.
+
+use std::{collections::HashMap, fs};
+
+use crate::units::Bytes;
+
+/// Memory counters (hugepage counts are in pages).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemInfo {
+    pub total: Bytes,
+    pub free: Bytes,
+    pub available: Bytes,
+    pub buffers: Bytes,
+    pub cached: Bytes,
+    pub shmem: Bytes,
+    pub slab_reclaimable: Bytes,
+    pub slab_unreclaimable: Bytes,
+    pub dirty: Bytes,
+    pub writeback: Bytes,
+    pub hugepages_total: u64,
+    pub hugepages_free: u64,
+    pub hugepage_size: Bytes,
+    pub committed: Bytes,
+    pub commit_limit: Bytes,
+}
+
+impl MemInfo {
+    /// Page cache excluding shared memory (which `Cached` also counts).
+    pub fn page_cache(&self) -> Bytes {
+        self.cached.saturating_sub(self.shmem)
+    }
+
+    /// Total slab allocations, reclaimable and not.
+    pub fn slab(&self) -> Bytes {
+        self.slab_reclaimable + self.slab_unreclaimable
+    }
+
+    /// Memory held by processes and the kernel that is not cache, buffers, slab or free.
+    pub fn used(&self) -> Bytes {
+        self.total
+            .saturating_sub(self.free)
+            .saturating_sub(self.buffers)
+            .saturating_sub(self.cached)
+            .saturating_sub(self.slab())
+    }
+}
+
+/// Tracks the latest `/proc/meminfo` snapshot.
+pub struct MemoryMonitor {
+    info: Option<MemInfo>,
+}
+
+impl MemoryMonitor {
+    /// Construct a new monitor and perform an initial refresh.
+    pub fn new() -> Self {
+        let mut monitor = Self { info: None };
+        monitor.refresh();
+        monitor
+    }
+
+    /// Re-read `/proc/meminfo`.
+    pub fn refresh(&mut self) {
+        self.info = fs::read_to_string("/proc/meminfo")
+            .ok()
+            .and_then(|text| parse_meminfo(&text));
+    }
+
+    /// Latest breakdown, or `None` when `/proc/meminfo` is unreadable.
+    pub fn info(&self) -> Option<&MemInfo> {
+        self.info.as_ref()
+    }
+}
+
+/// Parse `/proc/meminfo` into a key → raw value map (`kB` values stay in KiB).
+pub fn parse_meminfo_fields(text: &str) -> HashMap<&str, u64> {
+    text.lines()
+        .filter_map(|line| {
+            let (key, rest) = line.split_once(':')?;
+            let value = rest.split_whitespace().next()?.parse().ok()?;
+            Some((key.trim(), value))
+        })
+        .collect()
+}
+
+fn parse_meminfo(text: &str) -> Option<MemInfo> {
+    let fields = parse_meminfo_fields(text);
+    let get = |key: &str| fields.get(key).copied().unwrap_or(0);
+    let kib = |key: &str| Bytes::from_kib(get(key));
+    Some(MemInfo {
+        total: Bytes::from_kib(*fields.get("MemTotal")?),
+        free: kib("MemFree"),
+        available: kib("MemAvailable"),
+        buffers: kib("Buffers"),
+        cached: kib("Cached"),
+        shmem: kib("Shmem"),
+        slab_reclaimable: kib("SReclaimable"),
+        slab_unreclaimable: kib("SUnreclaim"),
+        dirty: kib("Dirty"),
+        writeback: kib("Writeback"),
+        hugepages_total: get("HugePages_Total"),
+        hugepages_free: get("HugePages_Free"),
+        hugepage_size: kib("Hugepagesize"),
+        committed: kib("Committed_AS"),
+        commit_limit: kib("CommitLimit"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "MemTotal:        6158152 kB\n\
+                          MemFree:         4395980 kB\n\
+                          MemAvailable:    5666584 kB\n\
+                          Buffers:           63780 kB\n\
+                          Cached:          1407252 kB\n\
+                          Dirty:             75788 kB\n\
+                          Writeback:             0 kB\n\
+                          Shmem:              9484 kB\n\
+                          SReclaimable:      35520 kB\n\
+                          SUnreclaim:        18540 kB\n\
+                          CommitLimit:     3079076 kB\n\
+                          Committed_AS:     335856 kB\n\
+                          HugePages_Total:       4\n\
+                          HugePages_Free:        1\n\
+                          Hugepagesize:       2048 kB\n";
+
+    #[test]
+    fn parse_meminfo_converts_kib_to_bytes() {
+        let info = parse_meminfo(SAMPLE).unwrap();
+        assert_eq!(info.total, Bytes::from_kib(6_158_152));
+        assert_eq!(info.dirty, Bytes::from_kib(75_788));
+        assert_eq!(info.hugepages_total, 4);
+        assert_eq!(info.hugepages_free, 1);
+        assert_eq!(info.hugepage_size, Bytes(2 * 1024 * 1024));
+        assert_eq!(info.commit_limit, Bytes::from_kib(3_079_076));
+        assert!(parse_meminfo("MemFree: 1 kB\n").is_none());
+    }
+
+    #[test]
+    fn breakdown_segments_sum_to_total() {
+        let info = parse_meminfo(SAMPLE).unwrap();
+        assert_eq!(info.page_cache(), Bytes::from_kib(1_407_252 - 9_484));
+        assert_eq!(info.slab(), Bytes::from_kib(35_520 + 18_540));
+        assert_eq!(
+            info.used() + info.free + info.buffers + info.page_cache() + info.shmem + info.slab(),
+            info.total
+        );
+    }
+}
@@ -0,0 +1,55 @@
+//! Bundles every collector so they are created and refreshed together.
+
+use crate::{
+    cgroup::tree::CgroupMonitor, cpu::CpuMonitor, docker::DockerMonitor, gpu::GpuMonitor,
+    kubernetes::KubeMonitor, memory::MemoryMonitor, pressure::PressureMonitor,
+    process::ProcessMonitor, swap::SwapMonitor, system::SystemMonitor,
+};
+
+/// All metric collectors sampled on each tick.
+pub struct Monitors {
+    pub cpu: CpuMonitor,
+    pub gpu: GpuMonitor,
+    pub system: SystemMonitor,
+    pub memory: MemoryMonitor,
+    pub swap: SwapMonitor,
+    pub pressure: PressureMonitor,
+    pub process: ProcessMonitor,
+    pub cgroups: CgroupMonitor,
+    pub docker: DockerMonitor,
+    pub kube: KubeMonitor,
+}
+
+impl Monitors {
+    /// Construct every monitor, each performing its initial refresh.
+    pub fn new() -> Self {
+        let mut monitors = Self {
+            cpu: CpuMonitor::new(),
+            gpu: GpuMonitor::new(),
+            system: SystemMonitor::new(),
+            memory: MemoryMonitor::new(),
+            swap: SwapMonitor::new(),
+            pressure: PressureMonitor::new(),
+            process: ProcessMonitor::new(),
+            cgroups: CgroupMonitor::new(),
+            docker: DockerMonitor::new(),
+            kube: KubeMonitor::new(),
+        };
+        monitors.kube.refresh(monitors.process.processes());
+        monitors
+    }
+
+    /// Refresh every monitor.
+    pub fn refresh(&mut self) {
+        self.cpu.refresh();
+        self.gpu.refresh();
+        self.system.refresh();
+        self.memory.refresh();
+        self.swap.refresh();
+        self.pressure.refresh();
+        self.process.refresh();
+        self.kube.refresh(self.process.processes());
+        self.cgroups.refresh();
+        self.docker.refresh();
+    }
+}
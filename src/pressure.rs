@@ -0,0 +1,247 @@
+//! Pressure Stall Information (PSI) for the system and rustop's own cgroup.
+
+use std::{collections::VecDeque, fs, path::PathBuf};
+
+use crate::cgroup;
+
+/// Number of avg10 samples retained for the pressure sparklines.
+pub const PRESSURE_HISTORY_LEN: usize = 60;
+
+/// Resource tracked by PSI.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resource {
+    Cpu,
+    Memory,
+    Io,
+}
+
+impl Resource {
+    pub const ALL: [Resource; 3] = [Resource::Cpu, Resource::Memory, Resource::Io];
+
+    /// File name stem used by both `/proc/pressure` and cgroup `*.pressure` files.
+    pub fn name(self) -> &'static str {
+        match self {
+            Resource::Cpu => "cpu",
+            Resource::Memory => "memory",
+            Resource::Io => "io",
+        }
+    }
+}
+
+/// One `some`/`full` line: stall percentages and total stall time in microseconds.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PressureLine {
+    pub avg10: f32,
+    pub avg60: f32,
+    pub avg300: f32,
+    pub total: u64,
+}
+
+/// Parsed contents of a pressure file.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Pressure {
+    pub some: Option<PressureLine>,
+    pub full: Option<PressureLine>,
+}
+
+/// Latest reading and avg10 history for one resource.
+#[derive(Debug, Clone, Default)]
+pub struct PressureTrack {
+    latest: Option<Pressure>,
+    some_history: VecDeque<f32>,
+    full_history: VecDeque<f32>,
+}
+
+impl PressureTrack {
+    /// Latest reading, `None` when the pressure file is unreadable.
+    pub fn latest(&self) -> Option<Pressure> {
+        self.latest
+    }
+
+    /// Recent `some` avg10 values, oldest first.
+    pub fn some_history(&self) -> &VecDeque<f32> {
+        &self.some_history
+    }
+
+    /// Recent `full` avg10 values, oldest first.
+    pub fn full_history(&self) -> &VecDeque<f32> {
+        &self.full_history
+    }
+
+    fn record(&mut self, pressure: Option<Pressure>) {
+        self.latest = pressure;
+        let some = pressure.and_then(|p| p.some).map_or(0.0, |l| l.avg10);
+        let full = pressure.and_then(|p| p.full).map_or(0.0, |l| l.avg10);
+        for (history, value) in [
+            (&mut self.some_history, some),
+            (&mut self.full_history, full),
+        ] {
+            if history.len() == PRESSURE_HISTORY_LEN {
+                history.pop_front();
+            }
+            history.push_back(value);
+        }
+    }
+}
+
+/// Pressure files for one scope (the whole system or a cgroup).
+#[derive(Debug, Clone)]
+pub struct PressureSet {
+    label: String,
+    paths: [PathBuf; 3],
+    tracks: [PressureTrack; 3],
+}
+
+impl PressureSet {
+    /// System-wide pressure from `/proc/pressure`.
+    pub fn system() -> Self {
+        Self::new(
+            "system".to_string(),
+            Resource::ALL.map(|r| PathBuf::from("/proc/pressure").join(r.name())),
+        )
+    }
+
+    /// Pressure of a cgroup v2 directory, labelled with its path relative to the root.
+    pub fn cgroup(label: String, dir: PathBuf) -> Self {
+        Self::new(
+            label,
+            Resource::ALL.map(|r| dir.join(format!("{}.pressure", r.name()))),
+        )
+    }
+
+    fn new(label: String, paths: [PathBuf; 3]) -> Self {
+        Self {
+            label,
+            paths,
+            tracks: Default::default(),
+        }
+    }
+
+    /// Re-read every pressure file and append to the history.
+    pub fn refresh(&mut self) {
+        for (path, track) in self.paths.iter().zip(self.tracks.iter_mut()) {
+            track.record(
+                fs::read_to_string(path)
+                    .ok()
+                    .map(|text| parse_pressure(&text)),
+            );
+        }
+    }
+
+    /// Human-readable scope name.
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// Reading and history for one resource.
+    pub fn track(&self, resource: Resource) -> &PressureTrack {
+        &self.tracks[resource as usize]
+    }
+}
+
+/// Tracks PSI for the whole system and, when available, rustop's own cgroup.
+pub struct PressureMonitor {
+    system: PressureSet,
+    cgroup: Option<PressureSet>,
+}
+
+impl PressureMonitor {
+    /// Construct a new monitor and perform an initial refresh.
+    pub fn new() -> Self {
+        let cgroup = cgroup::cgroup_root().and_then(|root| {
+            let dir = cgroup::process_cgroup_dir("self")?;
+            let relative = dir.strip_prefix(&root).ok()?.display().to_string();
+            // The root cgroup has no pressure files distinct from /proc/pressure.
+            (!relative.is_empty()).then(|| PressureSet::cgroup(format!("/{relative}"), dir))
+        });
+        let mut monitor = Self {
+            system: PressureSet::system(),
+            cgroup,
+        };
+        monitor.refresh();
+        monitor
+    }
+
+    /// Refresh system and cgroup pressure.
+    pub fn refresh(&mut self) {
+        self.system.refresh();
+        if let Some(cgroup) = self.cgroup.as_mut() {
+            cgroup.refresh();
+        }
+    }
+
+    /// System-wide pressure.
+    pub fn system(&self) -> &PressureSet {
+        &self.system
+    }
+
+    /// Pressure of the cgroup rustop runs in, if it is not the root cgroup.
+    pub fn cgroup(&self) -> Option<&PressureSet> {
+        self.cgroup.as_ref()
+    }
+}
+
+/// Parse a PSI file such as `/proc/pressure/io` or `memory.pressure`.
+pub fn parse_pressure(text: &str) -> Pressure {
+    let mut pressure = Pressure::default();
+    for line in text.lines() {
+        let mut fields = line.split_whitespace();
+        let kind = fields.next();
+        let mut parsed = PressureLine::default();
+        for field in fields {
+            match field.split_once('=') {
+                Some(("avg10", v)) => parsed.avg10 = v.parse().unwrap_or(0.0),
+                Some(("avg60", v)) => parsed.avg60 = v.parse().unwrap_or(0.0),
+                Some(("avg300", v)) => parsed.avg300 = v.parse().unwrap_or(0.0),
+                Some(("total", v)) => parsed.total = v.parse().unwrap_or(0),
+                _ => {}
+            }
+        }
+        match kind {
+            Some("some") => pressure.some = Some(parsed),
+            Some("full") => pressure.full = Some(parsed),
+            _ => {}
+        }
+    }
+    pressure
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_pressure_reads_some_and_full() {
+        let text = "some avg10=2.87 avg60=4.11 avg300=2.83 total=18553074\n\
+                    full avg10=0.00 avg60=0.02 avg300=0.13 total=2416895\n";
+        let pressure = parse_pressure(text);
+        assert_eq!(
+            pressure.some,
+            Some(PressureLine {
+                avg10: 2.87,
+                avg60: 4.11,
+                avg300: 2.83,
+                total: 18_553_074,
+            })
+        );
+        assert_eq!(pressure.full.unwrap().avg300, 0.13);
+        assert_eq!(parse_pressure("").some, None);
+    }
+
+    #[test]
+    fn track_history_is_bounded() {
+        let mut track = PressureTrack::default();
+        for i in 0..PRESSURE_HISTORY_LEN + 5 {
+            track.record(Some(Pressure {
+                some: Some(PressureLine {
+                    avg10: i as f32,
+                    ..PressureLine::default()
+                }),
+                full: None,
+            }));
+        }
+        assert_eq!(track.some_history().len(), PRESSURE_HISTORY_LEN);
+        assert_eq!(track.some_history().front(), Some(&5.0));
+        assert_eq!(track.full_history().back(), Some(&0.0));
+    }
+}
@@ -0,0 +1,1158 @@
+//! Process table collector backed by `/proc/<pid>`.
+
+pub mod anomaly;
+pub mod connector;
+pub mod control;
+pub mod detail;
+pub mod lifecycle;
+
+use std::{
+    collections::HashMap,
+    fs,
+    os::unix::fs::MetadataExt,
+    path::Path,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    cgroup, system,
+    units::{ByteRate, Bytes},
+};
+use anomaly::AnomalyDetector;
+use connector::ProcConnector;
+use detail::{parse_smaps_rollup, SmapsRollup};
+use lifecycle::LifecycleTracker;
+
+/// How often `smaps_rollup` is re-read; it walks every mapping so is far
+/// more expensive than `stat`.
+const SMAPS_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Snapshot of one process for the process table.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProcessInfo {
+    pub pid: u32,
+    pub ppid: u32,
+    pub name: String,
+    pub state: char,
+    pub uid: u32,
+    pub user: String,
+    /// CPU usage as a percentage of one CPU since the previous refresh.
+    pub cpu_percent: f32,
+    pub rss: Bytes,
+    /// PSS/USS/swap totals, present only while smaps collection is enabled
+    /// and the process is readable.
+    pub memory: Option<SmapsRollup>,
+    /// Storage read/write rates from `/proc/<pid>/io`, `None` when not permitted.
+    pub read_rate: Option<ByteRate>,
+    pub write_rate: Option<ByteRate>,
+    /// Traffic of the process's network namespace; only known for processes
+    /// outside the host namespace (containers, sandboxes).
+    pub net_rate: Option<ByteRate>,
+    pub threads: u32,
+    /// Start time as seconds since the Unix epoch.
+    pub start_time: u64,
+    pub command: String,
+    /// cgroup v2 path, e.g. `/system.slice/nginx.service`.
+    pub cgroup: Option<String>,
+}
+
+#[cfg(test)]
+impl ProcessInfo {
+    /// An idle, sleeping root process for tests to override with struct-update syntax.
+    pub fn test(pid: u32) -> Self {
+        Self {
+            pid,
+            ppid: 1,
+            name: format!("p{pid}"),
+            state: 'S',
+            uid: 0,
+            user: "root".into(),
+            cpu_percent: 0.0,
+            rss: Bytes(0),
+            memory: None,
+            read_rate: None,
+            write_rate: None,
+            net_rate: None,
+            threads: 1,
+            start_time: 0,
+            command: format!("p{pid}"),
+            cgroup: None,
+        }
+    }
+}
+
+/// Fields extracted from `/proc/<pid>/stat`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProcStat {
+    pub pid: u32,
+    pub comm: String,
+    pub state: char,
+    pub ppid: u32,
+    pub utime: u64,
+    pub stime: u64,
+    pub nice: i64,
+    pub num_threads: u32,
+    pub starttime: u64,
+    pub rss_pages: u64,
+    pub processor: Option<u32>,
+}
+
+impl ProcStat {
+    /// User plus system CPU time in clock ticks.
+    pub fn cpu_ticks(&self) -> u64 {
+        self.utime + self.stime
+    }
+}
+
+/// One thread of the focused process.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ThreadInfo {
+    pub tid: u32,
+    pub name: String,
+    pub state: char,
+    /// CPU usage as a percentage of one CPU since the previous refresh.
+    pub cpu_percent: f32,
+    /// CPU the thread last ran on.
+    pub processor: Option<u32>,
+}
+
+/// A process's share of one CPU during the last refresh.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CoreOccupant {
+    pub pid: u32,
+    pub name: String,
+    /// CPU time its threads last scheduled on this core used, in percent of the core.
+    pub cpu_percent: f32,
+}
+
+/// Most processes listed per core in the occupancy map.
+const OCCUPANTS_PER_CORE: usize = 3;
+
+/// Memory figure shown in the process table's memory column.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MemoryColumn {
+    /// Resident set size from `stat`; counts shared pages in full.
+    #[default]
+    Rss,
+    /// Proportional set size: shared pages split between their users.
+    Pss,
+    /// Unique set size: pages private to the process.
+    Uss,
+    /// Memory swapped out.
+    Swap,
+}
+
+impl MemoryColumn {
+    /// The column after this one, wrapping around.
+    pub fn next(self) -> Self {
+        match self {
+            MemoryColumn::Rss => MemoryColumn::Pss,
+            MemoryColumn::Pss => MemoryColumn::Uss,
+            MemoryColumn::Uss => MemoryColumn::Swap,
+            MemoryColumn::Swap => MemoryColumn::Rss,
+        }
+    }
+
+    /// Column header.
+    pub fn label(self) -> &'static str {
+        match self {
+            MemoryColumn::Rss => "RSS",
+            MemoryColumn::Pss => "PSS",
+            MemoryColumn::Uss => "USS",
+            MemoryColumn::Swap => "SWAP",
+        }
+    }
+
+    /// Whether this column needs `smaps_rollup` data.
+    pub fn needs_smaps(self) -> bool {
+        self != MemoryColumn::Rss
+    }
+
+    /// The value of this column for `process`, `None` when not collected.
+    pub fn value(self, process: &ProcessInfo) -> Option<Bytes> {
+        match self {
+            MemoryColumn::Rss => Some(process.rss),
+            MemoryColumn::Pss => process.memory.map(|m| m.pss),
+            MemoryColumn::Uss => process.memory.map(|m| m.uss),
+            MemoryColumn::Swap => process.memory.map(|m| m.swap),
+        }
+    }
+}
+
+/// Column the process table is ordered by.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SortKey {
+    #[default]
+    Cpu,
+    Memory,
+    DiskRead,
+    DiskWrite,
+    Network,
+    Pid,
+}
+
+impl SortKey {
+    /// The sort key after this one, wrapping around.
+    pub fn next(self) -> Self {
+        match self {
+            SortKey::Cpu => SortKey::Memory,
+            SortKey::Memory => SortKey::DiskRead,
+            SortKey::DiskRead => SortKey::DiskWrite,
+            SortKey::DiskWrite => SortKey::Network,
+            SortKey::Network => SortKey::Pid,
+            SortKey::Pid => SortKey::Cpu,
+        }
+    }
+
+    /// The next key that applies under `group_by`. Grouped rows skip network
+    /// order: members sharing a namespace would each count its whole traffic.
+    pub fn next_for(self, group_by: GroupBy) -> Self {
+        match self.next() {
+            SortKey::Network if group_by != GroupBy::None => SortKey::Pid,
+            next => next,
+        }
+    }
+}
+
+/// How the process table merges processes into groups.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum GroupBy {
+    /// One row per process.
+    #[default]
+    None,
+    User,
+    /// Executable name, merging worker processes of the same program.
+    Executable,
+    /// Innermost systemd slice, or the cgroup path outside systemd.
+    Cgroup,
+    /// systemd service or scope, e.g. `nginx.service` or `user@1000.service`.
+    Unit,
+}
+
+impl GroupBy {
+    /// The grouping after this one, wrapping around.
+    pub fn next(self) -> Self {
+        match self {
+            GroupBy::None => GroupBy::User,
+            GroupBy::User => GroupBy::Executable,
+            GroupBy::Executable => GroupBy::Cgroup,
+            GroupBy::Cgroup => GroupBy::Unit,
+            GroupBy::Unit => GroupBy::None,
+        }
+    }
+
+    /// Column header for the group key.
+    pub fn label(self) -> &'static str {
+        match self {
+            GroupBy::None => "PID",
+            GroupBy::User => "USER",
+            GroupBy::Executable => "EXECUTABLE",
+            GroupBy::Cgroup => "CGROUP",
+            GroupBy::Unit => "UNIT",
+        }
+    }
+
+    /// The group `process` belongs to.
+    fn key(self, process: &ProcessInfo) -> String {
+        match self {
+            GroupBy::None => process.pid.to_string(),
+            GroupBy::User => process.user.clone(),
+            GroupBy::Executable => executable_name(process).to_string(),
+            GroupBy::Cgroup => process
+                .cgroup
+                .as_deref()
+                .map(cgroup::slice_of)
+                .unwrap_or("?")
+                .to_string(),
+            GroupBy::Unit => process
+                .cgroup
+                .as_deref()
+                .map(cgroup::unit_of)
+                .unwrap_or("?")
+                .to_string(),
+        }
+    }
+}
+
+/// Totals for a group of processes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProcessGroup {
+    pub key: String,
+    pub count: usize,
+    /// Threads summed over member processes.
+    pub tasks: u32,
+    pub cpu_percent: f32,
+    /// Sum of the selected memory column over members that report it.
+    pub memory: Option<Bytes>,
+    pub gpu_memory: Bytes,
+    /// Summed storage rates over members that report them.
+    pub read_rate: Option<ByteRate>,
+    pub write_rate: Option<ByteRate>,
+}
+
+/// Cumulative storage I/O from `/proc/<pid>/io`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct IoCounters {
+    pub read_bytes: Bytes,
+    pub write_bytes: Bytes,
+}
+
+/// Kernel constants needed to interpret `/proc` values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProcClock {
+    pub ticks_per_sec: u64,
+    pub page_size: u64,
+    pub boot_time: u64,
+}
+
+impl ProcClock {
+    /// Query the running kernel.
+    pub fn detect() -> Self {
+        // SAFETY: sysconf has no preconditions and only reads system configuration.
+        let (ticks, page) = unsafe {
+            (
+                libc::sysconf(libc::_SC_CLK_TCK),
+                libc::sysconf(libc::_SC_PAGESIZE),
+            )
+        };
+        Self {
+            ticks_per_sec: u64::try_from(ticks).ok().filter(|t| *t > 0).unwrap_or(100),
+            page_size: u64::try_from(page).ok().filter(|p| *p > 0).unwrap_or(4096),
+            boot_time: system::read_boot_time().unwrap_or(0),
+        }
+    }
+
+    /// Convert a `starttime` in ticks since boot to seconds since the epoch.
+    pub fn start_epoch(&self, starttime_ticks: u64) -> u64 {
+        self.boot_time + starttime_ticks / self.ticks_per_sec
+    }
+}
+
+/// Tracks every process visible in `/proc` with per-refresh CPU usage.
+pub struct ProcessMonitor {
+    clock: ProcClock,
+    users: HashMap<u32, String>,
+    processes: Vec<ProcessInfo>,
+    previous_ticks: HashMap<u32, u64>,
+    last_refresh: Option<Instant>,
+    smaps_enabled: bool,
+    /// Last rollup per pid, `None` when it was unreadable, so failures also
+    /// wait for the next sweep.
+    smaps: HashMap<u32, Option<SmapsRollup>>,
+    last_smaps: Option<Instant>,
+    previous_io: HashMap<u32, IoCounters>,
+    previous_net: HashMap<String, Bytes>,
+    thread_focus: Option<u32>,
+    threads: Vec<ThreadInfo>,
+    previous_thread_ticks: HashMap<u32, u64>,
+    occupancy_enabled: bool,
+    occupancy: HashMap<u32, Vec<CoreOccupant>>,
+    previous_task_ticks: HashMap<u32, u64>,
+    lifecycle: LifecycleTracker,
+    anomalies: AnomalyDetector,
+}
+
+impl ProcessMonitor {
+    /// Construct a new monitor and perform an initial refresh.
+    pub fn new() -> Self {
+        let mut monitor = Self {
+            clock: ProcClock::detect(),
+            users: read_users(),
+            processes: Vec::new(),
+            previous_ticks: HashMap::new(),
+            last_refresh: None,
+            smaps_enabled: false,
+            smaps: HashMap::new(),
+            last_smaps: None,
+            previous_io: HashMap::new(),
+            previous_net: HashMap::new(),
+            thread_focus: None,
+            threads: Vec::new(),
+            previous_thread_ticks: HashMap::new(),
+            occupancy_enabled: false,
+            occupancy: HashMap::new(),
+            previous_task_ticks: HashMap::new(),
+            lifecycle: LifecycleTracker::new(ProcConnector::open()),
+            anomalies: AnomalyDetector::new(anomaly::D_STATE_SECS),
+        };
+        monitor.refresh();
+        monitor
+    }
+
+    /// Re-scan `/proc` and compute CPU and I/O rates since the previous scan.
+    pub fn refresh(&mut self) {
+        let now = Instant::now();
+        let elapsed = self
+            .last_refresh
+            .map(|at| now.duration_since(at).as_secs_f64())
+            .unwrap_or(0.0);
+        let smaps_due = self.smaps_enabled
+            && self
+                .last_smaps
+                .is_none_or(|at| now.duration_since(at) >= SMAPS_INTERVAL);
+
+        let host_netns = fs::read_link("/proc/self/ns/net").ok();
+        let mut ticks = HashMap::with_capacity(self.previous_ticks.len());
+        let mut io = HashMap::with_capacity(self.previous_io.len());
+        let mut net: HashMap<String, Bytes> = HashMap::new();
+        let mut processes = Vec::with_capacity(self.processes.len());
+        for pid in list_pids(Path::new("/proc")) {
+            let dir = Path::new("/proc").join(pid.to_string());
+            let Some(stat) = fs::read_to_string(dir.join("stat"))
+                .ok()
+                .and_then(|text| parse_stat(&text))
+            else {
+                continue;
+            };
+            let uid = fs::metadata(&dir).map(|m| m.uid()).unwrap_or(0);
+
+            let cpu_percent = match self.previous_ticks.get(&pid) {
+                Some(&before) if elapsed > 0.0 => {
+                    let delta = stat.cpu_ticks().saturating_sub(before) as f64;
+                    (delta / self.clock.ticks_per_sec as f64 / elapsed * 100.0) as f32
+                }
+                _ => 0.0,
+            };
+            ticks.insert(pid, stat.cpu_ticks());
+
+            let command = fs::read(dir.join("cmdline"))
+                .ok()
+                .map(|raw| split_nul(&raw).join(" "))
+                .filter(|cmd| !cmd.is_empty())
+                .unwrap_or_else(|| format!("[{}]", stat.comm));
+
+            // Between sweeps, only processes not seen since the last one are read.
+            let memory = match self.smaps.get(&pid) {
+                _ if !self.smaps_enabled => None,
+                Some(cached) if !smaps_due => *cached,
+                _ => fs::read_to_string(dir.join("smaps_rollup"))
+                    .ok()
+                    .and_then(|text| parse_smaps_rollup(&text)),
+            };
+
+            let (read_rate, write_rate) = match fs::read_to_string(dir.join("io"))
+                .ok()
+                .and_then(|text| parse_io(&text))
+            {
+                Some(counters) => {
+                    let rates = self.previous_io.get(&pid).map(|before| {
+                        (
+                            ByteRate::between(before.read_bytes, counters.read_bytes, elapsed),
+                            ByteRate::between(before.write_bytes, counters.write_bytes, elapsed),
+                        )
+                    });
+                    io.insert(pid, counters);
+                    rates.unwrap_or((None, None))
+                }
+                None => (None, None),
+            };
+
+            // Each namespace's counters are read once per refresh.
+            let net_rate = fs::read_link(dir.join("ns/net"))
+                .ok()
+                .filter(|ns| Some(ns) != host_netns.as_ref())
+                .and_then(|ns| {
+                    let ns = ns.display().to_string();
+                    let total = match net.get(&ns) {
+                        Some(total) => *total,
+                        None => {
+                            let total = fs::read_to_string(dir.join("net/dev"))
+                                .ok()
+                                .map(|text| parse_net_dev_total(&text))?;
+                            net.insert(ns.clone(), total);
+                            total
+                        }
+                    };
+                    ByteRate::between(*self.previous_net.get(&ns)?, total, elapsed)
+                });
+
+            processes.push(ProcessInfo {
+                pid,
+                ppid: stat.ppid,
+                user: self.user_name(uid),
+                uid,
+                state: stat.state,
+                cpu_percent,
+                rss: Bytes(stat.rss_pages.saturating_mul(self.clock.page_size)),
+                memory,
+                read_rate,
+                write_rate,
+                net_rate,
+                threads: stat.num_threads,
+                start_time: self.clock.start_epoch(stat.starttime),
+                command,
+                cgroup: fs::read_to_string(dir.join("cgroup"))
+                    .ok()
+                    .and_then(|text| cgroup::parse_proc_cgroup(&text).map(str::to_string)),
+                name: stat.comm,
+            });
+        }
+
+        processes.sort_by(|a, b| {
+            b.cpu_percent
+                .total_cmp(&a.cpu_percent)
+                .then(a.pid.cmp(&b.pid))
+        });
+        if self.smaps_enabled {
+            self.smaps = processes.iter().map(|p| (p.pid, p.memory)).collect();
+        }
+        if smaps_due {
+            self.last_smaps = Some(now);
+        }
+        self.processes = processes;
+        self.previous_ticks = ticks;
+        self.previous_io = io;
+        self.previous_net = net;
+        self.refresh_threads(elapsed);
+        self.refresh_occupancy(elapsed);
+        let epoch = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        self.lifecycle.update(&self.processes, epoch);
+        self.anomalies.update(&self.processes, epoch);
+        self.last_refresh = Some(now);
+    }
+
+    /// Attribute every thread's CPU time since the last refresh to the core it
+    /// last ran on, and keep the heaviest processes per core.
+    fn refresh_occupancy(&mut self, elapsed: f64) {
+        if !self.occupancy_enabled {
+            return;
+        }
+        let mut ticks = HashMap::with_capacity(self.previous_task_ticks.len());
+        let mut samples = Vec::new();
+        for process in &self.processes {
+            let task_dir = Path::new("/proc")
+                .join(process.pid.to_string())
+                .join("task");
+            for tid in list_pids(&task_dir) {
+                let Some(stat) = fs::read_to_string(task_dir.join(tid.to_string()).join("stat"))
+                    .ok()
+                    .and_then(|text| parse_stat(&text))
+                else {
+                    continue;
+                };
+                ticks.insert(tid, stat.cpu_ticks());
+                let (Some(cpu), Some(&before)) =
+                    (stat.processor, self.previous_task_ticks.get(&tid))
+                else {
+                    continue;
+                };
+                samples.push((cpu, process.pid, stat.cpu_ticks().saturating_sub(before)));
+            }
+        }
+        self.occupancy = occupancy(
+            samples,
+            &self.processes,
+            self.clock.ticks_per_sec as f64 * elapsed,
+        );
+        self.previous_task_ticks = ticks;
+    }
+
+    /// Enable or disable the per-core occupancy scan of every thread.
+    pub fn set_occupancy_enabled(&mut self, enabled: bool) {
+        if self.occupancy_enabled != enabled {
+            self.occupancy_enabled = enabled;
+            self.occupancy.clear();
+            self.previous_task_ticks.clear();
+        }
+    }
+
+    /// Heaviest processes last scheduled on `cpu`, highest first.
+    pub fn occupants(&self, cpu: u32) -> &[CoreOccupant] {
+        self.occupancy.get(&cpu).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// Re-read the threads of the focused process.
+    fn refresh_threads(&mut self, elapsed: f64) {
+        let Some(pid) = self.thread_focus else {
+            self.threads.clear();
+            self.previous_thread_ticks.clear();
+            return;
+        };
+        let task_dir = Path::new("/proc").join(pid.to_string()).join("task");
+        (self.threads, self.previous_thread_ticks) = read_threads(
+            &task_dir,
+            &self.previous_thread_ticks,
+            self.clock.ticks_per_sec as f64 * elapsed,
+        );
+    }
+
+    /// Track the threads of `pid` (or none) from the next refresh on.
+    pub fn set_thread_focus(&mut self, pid: Option<u32>) {
+        if self.thread_focus != pid {
+            self.thread_focus = pid;
+            self.threads.clear();
+            self.previous_thread_ticks.clear();
+        }
+    }
+
+    /// Threads of the focused process, ordered by thread id.
+    pub fn threads(&self) -> &[ThreadInfo] {
+        &self.threads
+    }
+
+    /// CPUs the focused process's threads last ran on, ascending and deduplicated.
+    pub fn occupied_cpus(&self) -> Vec<u32> {
+        let mut cpus: Vec<u32> = self.threads.iter().filter_map(|t| t.processor).collect();
+        cpus.sort_unstable();
+        cpus.dedup();
+        cpus
+    }
+
+    /// Enable or disable reading `smaps_rollup` for PSS/USS/swap.
+    pub fn set_smaps_enabled(&mut self, enabled: bool) {
+        if self.smaps_enabled != enabled {
+            self.smaps_enabled = enabled;
+            self.smaps.clear();
+            self.last_smaps = None;
+        }
+    }
+
+    /// Flag processes blocked in `D` state for more than `secs` seconds.
+    pub fn set_d_state_secs(&mut self, secs: u64) {
+        self.anomalies.set_d_state_secs(secs);
+    }
+
+    /// Processes ordered by CPU usage, highest first.
+    pub fn processes(&self) -> &[ProcessInfo] {
+        &self.processes
+    }
+
+    /// Process start/exit tracking and event log.
+    pub fn lifecycle(&self) -> &LifecycleTracker {
+        &self.lifecycle
+    }
+
+    /// Zombies, long `D` sleeps and steadily growing processes.
+    pub fn anomalies(&self) -> &AnomalyDetector {
+        &self.anomalies
+    }
+
+    /// Kernel clock and page size used to interpret `/proc` values.
+    pub fn clock(&self) -> ProcClock {
+        self.clock
+    }
+
+    /// Resolve a uid to a user name, falling back to the numeric id.
+    pub fn user_name(&self, uid: u32) -> String {
+        self.users
+            .get(&uid)
+            .cloned()
+            .unwrap_or_else(|| uid.to_string())
+    }
+}
+
+/// Read every thread under `task_dir`, ordered by thread id, with CPU usage
+/// against the `previous` tick counts. `ticks_per_core` is what one fully busy
+/// core accrues over the interval. Also returns the counts for the next call.
+fn read_threads(
+    task_dir: &Path,
+    previous: &HashMap<u32, u64>,
+    ticks_per_core: f64,
+) -> (Vec<ThreadInfo>, HashMap<u32, u64>) {
+    let mut ticks = HashMap::with_capacity(previous.len());
+    let mut threads: Vec<ThreadInfo> = list_pids(task_dir)
+        .into_iter()
+        .filter_map(|tid| {
+            let stat = fs::read_to_string(task_dir.join(tid.to_string()).join("stat"))
+                .ok()
+                .and_then(|text| parse_stat(&text))?;
+            let cpu_percent = match previous.get(&tid) {
+                Some(&before) if ticks_per_core > 0.0 => {
+                    let delta = stat.cpu_ticks().saturating_sub(before) as f64;
+                    (delta / ticks_per_core * 100.0) as f32
+                }
+                _ => 0.0,
+            };
+            ticks.insert(tid, stat.cpu_ticks());
+            Some(ThreadInfo {
+                tid,
+                name: stat.comm,
+                state: stat.state,
+                cpu_percent,
+                processor: stat.processor,
+            })
+        })
+        .collect();
+    threads.sort_by_key(|thread| thread.tid);
+    (threads, ticks)
+}
+
+/// Sum `(cpu, pid, ticks)` thread samples per core and process, keeping the
+/// heaviest [`OCCUPANTS_PER_CORE`] processes on each core. `ticks_per_core` is
+/// what one fully busy core accrues over the interval.
+fn occupancy(
+    samples: impl IntoIterator<Item = (u32, u32, u64)>,
+    processes: &[ProcessInfo],
+    ticks_per_core: f64,
+) -> HashMap<u32, Vec<CoreOccupant>> {
+    let mut per_core: HashMap<u32, HashMap<u32, u64>> = HashMap::new();
+    for (cpu, pid, delta) in samples.into_iter().filter(|sample| sample.2 > 0) {
+        *per_core.entry(cpu).or_default().entry(pid).or_default() += delta;
+    }
+    let names: HashMap<u32, &str> = processes.iter().map(|p| (p.pid, p.name.as_str())).collect();
+    per_core
+        .into_iter()
+        .map(|(cpu, pids)| {
+            let mut occupants: Vec<CoreOccupant> = pids
+                .into_iter()
+                .map(|(pid, delta)| CoreOccupant {
+                    pid,
+                    name: names.get(&pid).copied().unwrap_or("?").to_string(),
+                    cpu_percent: if ticks_per_core > 0.0 {
+                        (delta as f64 / ticks_per_core * 100.0) as f32
+                    } else {
+                        0.0
+                    },
+                })
+                .collect();
+            occupants.sort_by(|a, b| {
+                b.cpu_percent
+                    .total_cmp(&a.cpu_percent)
+                    .then(a.pid.cmp(&b.pid))
+            });
+            occupants.truncate(OCCUPANTS_PER_CORE);
+            (cpu, occupants)
+        })
+        .collect()
+}
+
+/// Numeric directory names under a procfs root.
+pub fn list_pids(proc_root: &Path) -> Vec<u32> {
+    let Ok(entries) = fs::read_dir(proc_root) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter_map(|entry| entry.file_name().to_str()?.parse().ok())
+        .collect()
+}
+
+/// Parse `/proc/<pid>/stat`, handling command names that contain spaces or parentheses.
+pub fn parse_stat(text: &str) -> Option<ProcStat> {
+    let open = text.find('(')?;
+    let close = text.rfind(')')?;
+    let pid = text[..open].trim().parse().ok()?;
+    let comm = text[open + 1..close].to_string();
+    // Fields after the command name, starting with field 3 (state).
+    let rest: Vec<&str> = text[close + 1..].split_whitespace().collect();
+    let field = |n: usize| rest.get(n - 3).copied();
+    let number = |n: usize| field(n)?.parse::<u64>().ok();
+
+    Some(ProcStat {
+        pid,
+        comm,
+        state: field(3)?.chars().next()?,
+        ppid: number(4)? as u32,
+        utime: number(14)?,
+        stime: number(15)?,
+        nice: field(19)?.parse().ok()?,
+        num_threads: number(20)? as u32,
+        starttime: number(22)?,
+        rss_pages: number(24)?,
+        processor: number(39).map(|cpu| cpu as u32),
+    })
+}
+
+/// Order processes by `key`; memory uses the column currently displayed.
+/// Metrics sort highest first, PIDs ascending, and ties fall back to PID.
+pub fn sorted(processes: &[ProcessInfo], key: SortKey, memory: MemoryColumn) -> Vec<&ProcessInfo> {
+    let rate = |rate: Option<ByteRate>| rate.map_or(-1.0, |r| r.0);
+    let mut sorted: Vec<&ProcessInfo> = processes.iter().collect();
+    sorted.sort_by(|a, b| {
+        let order = match key {
+            SortKey::Cpu => b.cpu_percent.total_cmp(&a.cpu_percent),
+            SortKey::Memory => memory.value(b).cmp(&memory.value(a)),
+            SortKey::DiskRead => rate(b.read_rate).total_cmp(&rate(a.read_rate)),
+            SortKey::DiskWrite => rate(b.write_rate).total_cmp(&rate(a.write_rate)),
+            SortKey::Network => rate(b.net_rate).total_cmp(&rate(a.net_rate)),
+            SortKey::Pid => std::cmp::Ordering::Equal,
+        };
+        order.then(a.pid.cmp(&b.pid))
+    });
+    sorted
+}
+
+/// Merge processes into groups, ordered by CPU (or memory, disk I/O, or name
+/// when sorting by PID).
+pub fn group_processes(
+    processes: &[ProcessInfo],
+    group_by: GroupBy,
+    key: SortKey,
+    memory: MemoryColumn,
+    gpu_memory: impl Fn(u32) -> Option<Bytes>,
+) -> Vec<ProcessGroup> {
+    let mut groups: HashMap<String, ProcessGroup> = HashMap::new();
+    for process in processes {
+        let name = group_by.key(process);
+        let group = groups.entry(name.clone()).or_insert_with(|| ProcessGroup {
+            key: name,
+            count: 0,
+            tasks: 0,
+            cpu_percent: 0.0,
+            memory: None,
+            gpu_memory: Bytes(0),
+            read_rate: None,
+            write_rate: None,
+        });
+        group.count += 1;
+        group.tasks += process.threads;
+        group.cpu_percent += process.cpu_percent;
+        if let Some(bytes) = memory.value(process) {
+            group.memory = Some(group.memory.unwrap_or_default() + bytes);
+        }
+        group.gpu_memory += gpu_memory(process.pid).unwrap_or_default();
+        let add = |total: Option<ByteRate>, rate: Option<ByteRate>| match rate {
+            Some(rate) => Some(ByteRate(total.map_or(0.0, |t| t.0) + rate.0)),
+            None => total,
+        };
+        group.read_rate = add(group.read_rate, process.read_rate);
+        group.write_rate = add(group.write_rate, process.write_rate);
+    }
+
+    let rate = |rate: Option<ByteRate>| rate.map_or(-1.0, |r| r.0);
+    let mut groups: Vec<ProcessGroup> = groups.into_values().collect();
+    groups.sort_by(|a, b| {
+        let order = match key {
+            SortKey::Memory => b.memory.cmp(&a.memory),
+            SortKey::DiskRead => rate(b.read_rate).total_cmp(&rate(a.read_rate)),
+            SortKey::DiskWrite => rate(b.write_rate).total_cmp(&rate(a.write_rate)),
+            SortKey::Pid => std::cmp::Ordering::Equal,
+            _ => b.cpu_percent.total_cmp(&a.cpu_percent),
+        };
+        order.then_with(|| a.key.cmp(&b.key))
+    });
+    groups
+}
+
+/// Program name used for grouping: the basename of `argv[0]`, or the kernel
+/// name for kernel threads and processes with an empty command line.
+pub fn executable_name(process: &ProcessInfo) -> &str {
+    if process.command.starts_with('[') {
+        return &process.name;
+    }
+    process
+        .command
+        .split_whitespace()
+        .next()
+        .and_then(|argv0| argv0.rsplit('/').next())
+        .filter(|name| !name.is_empty())
+        .unwrap_or(&process.name)
+}
+
+/// Parse the storage counters from `/proc/<pid>/io`.
+pub fn parse_io(text: &str) -> Option<IoCounters> {
+    let mut read_bytes = None;
+    let mut write_bytes = None;
+    for line in text.lines() {
+        match line.split_once(':') {
+            Some(("read_bytes", value)) => read_bytes = value.trim().parse().ok(),
+            Some(("write_bytes", value)) => write_bytes = value.trim().parse().ok(),
+            _ => {}
+        }
+    }
+    Some(IoCounters {
+        read_bytes: Bytes(read_bytes?),
+        write_bytes: Bytes(write_bytes?),
+    })
+}
+
+/// Received plus transmitted bytes over every non-loopback interface in a
+/// `net/dev` table.
+pub fn parse_net_dev_total(text: &str) -> Bytes {
+    text.lines()
+        .skip(2)
+        .filter_map(|line| {
+            let (name, counters) = line.split_once(':')?;
+            if name.trim() == "lo" {
+                return None;
+            }
+            let fields: Vec<u64> = counters
+                .split_whitespace()
+                .filter_map(|v| v.parse().ok())
+                .collect();
+            Some(Bytes(fields.first()? + fields.get(8)?))
+        })
+        .sum()
+}
+
+/// Split a NUL-separated `/proc` file (cmdline, environ) into strings.
+pub fn split_nul(raw: &[u8]) -> Vec<String> {
+    raw.split(|b| *b == 0)
+        .filter(|part| !part.is_empty())
+        .map(|part| String::from_utf8_lossy(part).into_owned())
+        .collect()
+}
+
+fn read_users() -> HashMap<u32, String> {
+    fs::read_to_string("/etc/passwd")
+        .map(|text| parse_passwd(&text))
+        .unwrap_or_default()
+}
+
+fn parse_passwd(text: &str) -> HashMap<u32, String> {
+    text.lines()
+        .filter_map(|line| {
+            let mut fields = line.split(':');
+            let name = fields.next()?;
+            let uid = fields.nth(1)?.parse().ok()?;
+            Some((uid, name.to_string()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDir;
+
+    #[test]
+    fn parse_stat_handles_parenthesised_names() {
+        let text = "8950 (my (odd) cmd) R 8946 8950 8946 0 -1 4194304 79 0 0 0 \
+                    12 5 0 0 20 -5 3 0 87314 2703360 284 18446744073709551615 \
+                    1 1 1 0 0 0 0 0 0 0 0 0 17 6 0 0 0 0 0";
+        let stat = parse_stat(text).unwrap();
+        assert_eq!(stat.pid, 8950);
+        assert_eq!(stat.comm, "my (odd) cmd");
+        assert_eq!(stat.state, 'R');
+        assert_eq!(stat.ppid, 8946);
+        assert_eq!(stat.cpu_ticks(), 17);
+        assert_eq!(stat.nice, -5);
+        assert_eq!(stat.num_threads, 3);
+        assert_eq!(stat.starttime, 87314);
+        assert_eq!(stat.rss_pages, 284);
+        assert_eq!(stat.processor, Some(6));
+        assert!(parse_stat("garbage").is_none());
+    }
+
+    #[test]
+    fn split_nul_and_passwd() {
+        assert_eq!(
+            split_nul(b"python\0-m\0train\0"),
+            vec!["python", "-m", "train"]
+        );
+        assert!(split_nul(b"").is_empty());
+
+        let users = parse_passwd(
+            "root:x:0:0:root:/root:/bin/bash\nalice:x:1000:1000::/home/alice:/bin/sh\n",
+        );
+        assert_eq!(users.get(&1000).map(String::as_str), Some("alice"));
+        assert_eq!(users.get(&0).map(String::as_str), Some("root"));
+    }
+
+    #[test]
+    fn start_epoch_uses_boot_time() {
+        let clock = ProcClock {
+            ticks_per_sec: 100,
+            page_size: 4096,
+            boot_time: 1_000,
+        };
+        assert_eq!(clock.start_epoch(12_345), 1_123);
+    }
+
+    #[test]
+    fn memory_column_values_need_smaps() {
+        let mut process = ProcessInfo {
+            rss: Bytes(4096),
+            ..ProcessInfo::test(1)
+        };
+        assert_eq!(MemoryColumn::Rss.value(&process), Some(Bytes(4096)));
+        assert_eq!(MemoryColumn::Pss.value(&process), None);
+
+        process.memory = Some(SmapsRollup {
+            pss: Bytes(1024),
+            uss: Bytes(512),
+            swap: Bytes(256),
+            ..SmapsRollup::default()
+        });
+        assert_eq!(MemoryColumn::Pss.value(&process), Some(Bytes(1024)));
+        assert_eq!(MemoryColumn::Uss.value(&process), Some(Bytes(512)));
+        assert_eq!(MemoryColumn::Swap.value(&process), Some(Bytes(256)));
+        assert_eq!(MemoryColumn::Swap.next(), MemoryColumn::Rss);
+        assert!(!MemoryColumn::Rss.needs_smaps());
+    }
+
+    #[test]
+    fn parse_io_and_net_dev() {
+        let io = "rchar: 323934931\nwchar: 323929600\nsyscr: 632687\nsyscw: 632675\n\
+                  read_bytes: 4096\nwrite_bytes: 323932160\ncancelled_write_bytes: 0\n";
+        assert_eq!(
+            parse_io(io),
+            Some(IoCounters {
+                read_bytes: Bytes(4096),
+                write_bytes: Bytes(323932160),
+            })
+        );
+        assert!(parse_io("rchar: 1\n").is_none());
+
+        let dev = "Inter-|   Receive                                                |  Transmit\n \
+                   face |bytes    packets errs drop fifo frame compressed multicast|bytes    packets errs drop fifo colls carrier compressed\n    \
+                   lo:  5000      10    0    0    0     0          0         0     5000      10    0    0    0     0       0          0\n  \
+                   eth0:  1200      12    0    0    0     0          0         0      800       9    0    0    0     0       0          0\n";
+        assert_eq!(parse_net_dev_total(dev), Bytes(2000));
+    }
+
+    #[test]
+    fn sorted_orders_by_key_with_pid_ties() {
+        let make = |pid: u32, cpu: f32, write: Option<f64>| ProcessInfo {
+            cpu_percent: cpu,
+            rss: Bytes(u64::from(pid)),
+            write_rate: write.map(ByteRate),
+            ..ProcessInfo::test(pid)
+        };
+        let processes = [
+            make(3, 5.0, None),
+            make(1, 5.0, Some(10.0)),
+            make(2, 9.0, Some(0.0)),
+        ];
+        let pids = |key| {
+            sorted(&processes, key, MemoryColumn::Rss)
+                .iter()
+                .map(|p| p.pid)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(pids(SortKey::Cpu), vec![2, 1, 3]);
+        assert_eq!(pids(SortKey::Memory), vec![3, 2, 1]);
+        assert_eq!(pids(SortKey::DiskWrite), vec![1, 2, 3]);
+        assert_eq!(pids(SortKey::Pid), vec![1, 2, 3]);
+
+        assert_eq!(SortKey::DiskWrite.next_for(GroupBy::None), SortKey::Network);
+        assert_eq!(SortKey::DiskWrite.next_for(GroupBy::User), SortKey::Pid);
+    }
+
+    #[test]
+    fn read_threads_computes_usage_from_task_stats() {
+        let task_dir = TempDir::new("task");
+        let write = |tid: u32, name: &str, utime: u64, processor: u32| {
+            let stat = format!(
+                "{tid} ({name}) R 1 1 1 0 -1 0 0 0 0 0 {utime} 0 0 0 20 0 2 0 100 0 10 0 \
+                 1 1 1 0 0 0 0 0 0 0 0 0 17 {processor} 0 0 0 0 0"
+            );
+            task_dir.write(&format!("{tid}/stat"), stat);
+        };
+        write(102, "worker", 10, 3);
+        write(101, "main", 40, 1);
+
+        let (first, ticks) = read_threads(task_dir.path(), &HashMap::new(), 100.0);
+        write(102, "worker", 60, 2);
+        let (second, _) = read_threads(task_dir.path(), &ticks, 100.0);
+
+        assert_eq!(first.iter().map(|t| t.tid).collect::<Vec<_>>(), [101, 102]);
+        assert!(first.iter().all(|t| t.cpu_percent == 0.0));
+        assert_eq!(
+            second[1],
+            ThreadInfo {
+                tid: 102,
+                name: "worker".into(),
+                state: 'R',
+                cpu_percent: 50.0,
+                processor: Some(2),
+            }
+        );
+        assert_eq!(second[0].cpu_percent, 0.0);
+    }
+
+    #[test]
+    fn occupancy_sums_threads_per_core_and_keeps_the_heaviest() {
+        let processes = [1, 2, 3, 4].map(ProcessInfo::test);
+        // Two threads of pid 1 on cpu 0; four processes sharing cpu 1.
+        let samples = [
+            (0, 1, 30),
+            (0, 1, 20),
+            (0, 2, 0),
+            (1, 1, 5),
+            (1, 2, 40),
+            (1, 3, 10),
+            (1, 4, 10),
+            (1, 9, 25),
+        ];
+        let cores = occupancy(samples, &processes, 100.0);
+
+        assert_eq!(
+            cores[&0],
+            vec![CoreOccupant {
+                pid: 1,
+                name: "p1".into(),
+                cpu_percent: 50.0,
+            }]
+        );
+        let on_one: Vec<(u32, &str, f32)> = cores[&1]
+            .iter()
+            .map(|o| (o.pid, o.name.as_str(), o.cpu_percent))
+            .collect();
+        assert_eq!(
+            on_one,
+            vec![(2, "p2", 40.0), (9, "?", 25.0), (3, "p3", 10.0)]
+        );
+        assert_eq!(occupancy(samples, &processes, 0.0)[&0][0].cpu_percent, 0.0);
+    }
+
+    #[test]
+    fn group_processes_sums_by_executable_and_user() {
+        let make = |pid: u32, user: &str, command: &str, cpu: f32| ProcessInfo {
+            name: "worker".into(),
+            user: user.into(),
+            cpu_percent: cpu,
+            rss: Bytes(100),
+            command: command.into(),
+            cgroup: Some("/user.slice/user-1000.slice/session-2.scope".into()),
+            ..ProcessInfo::test(pid)
+        };
+        let processes = [
+            make(10, "alice", "/usr/bin/python3 train.py", 50.0),
+            make(11, "alice", "python3 -m loader", 25.0),
+            make(12, "bob", "[kworker/0:1]", 5.0),
+        ];
+        let gpu = |pid: u32| (pid == 10).then_some(Bytes(2048));
+
+        let by_exe = group_processes(
+            &processes,
+            GroupBy::Executable,
+            SortKey::Cpu,
+            MemoryColumn::Rss,
+            gpu,
+        );
+        assert_eq!(by_exe.len(), 2);
+        assert_eq!(by_exe[0].key, "python3");
+        assert_eq!(by_exe[0].count, 2);
+        assert_eq!(by_exe[0].cpu_percent, 75.0);
+        assert_eq!(by_exe[0].memory, Some(Bytes(200)));
+        assert_eq!(by_exe[0].gpu_memory, Bytes(2048));
+        assert_eq!(by_exe[1].key, "worker");
+
+        let by_user = group_processes(
+            &processes,
+            GroupBy::User,
+            SortKey::Pid,
+            MemoryColumn::Pss,
+            gpu,
+        );
+        assert_eq!(
+            by_user.iter().map(|g| g.key.as_str()).collect::<Vec<_>>(),
+            vec!["alice", "bob"]
+        );
+        assert_eq!(by_user[0].memory, None);
+
+        let by_cgroup = group_processes(
+            &processes,
+            GroupBy::Cgroup,
+            SortKey::Cpu,
+            MemoryColumn::Rss,
+            gpu,
+        );
+        assert_eq!(by_cgroup[0].key, "user-1000.slice");
+        assert_eq!(by_cgroup[0].count, 3);
+
+        let by_unit = group_processes(
+            &processes,
+            GroupBy::Unit,
+            SortKey::Cpu,
+            MemoryColumn::Rss,
+            gpu,
+        );
+        assert_eq!(by_unit[0].key, "session-2.scope");
+        assert_eq!(by_unit[0].tasks, 3);
+    }
+}
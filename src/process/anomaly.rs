@@ -0,0 +1,291 @@
+//! Flags zombies, long uninterruptible sleeps and steadily growing processes.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    fs,
+};
+
+use crate::{process::ProcessInfo, units::Bytes};
+
+/// Default seconds a process must stay in `D` state before it is flagged.
+pub const D_STATE_SECS: u64 = 10;
+/// Seconds between RSS/fd samples used for growth detection.
+const SAMPLE_INTERVAL_SECS: u64 = 5;
+/// Samples in the growth window (two minutes at the sample interval).
+pub const GROWTH_WINDOW: usize = 24;
+/// Smallest RSS increase over the window worth reporting.
+const MIN_RSS_GROWTH: u64 = 4 * 1024 * 1024;
+/// Smallest open-fd increase over the window worth reporting.
+const MIN_FD_GROWTH: u64 = 16;
+
+/// Why a process was flagged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnomalyKind {
+    /// Exited but not yet reaped by its parent.
+    Zombie { ppid: u32 },
+    /// In uninterruptible sleep for `secs` seconds.
+    Blocked { secs: u64 },
+    /// RSS never shrank across the whole window.
+    RssGrowth { from: Bytes, to: Bytes },
+    /// Open file descriptor count never shrank across the whole window.
+    FdGrowth { from: u64, to: u64 },
+}
+
+/// One flagged process.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Anomaly {
+    pub pid: u32,
+    pub name: String,
+    pub kind: AnomalyKind,
+    /// Kernel function the process is waiting in, from `/proc/<pid>/wchan`.
+    pub wchan: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Sample {
+    rss: u64,
+    fds: Option<u64>,
+}
+
+/// Tracks per-process state over time and reports anomalies.
+pub struct AnomalyDetector {
+    /// Seconds in `D` state after which a process is flagged.
+    d_state_secs: u64,
+    blocked_since: HashMap<u32, u64>,
+    samples: HashMap<u32, VecDeque<Sample>>,
+    last_sample: Option<u64>,
+    anomalies: Vec<Anomaly>,
+}
+
+impl AnomalyDetector {
+    /// Create a detector with no history that flags processes blocked for
+    /// more than `d_state_secs`.
+    pub fn new(d_state_secs: u64) -> Self {
+        Self {
+            d_state_secs,
+            blocked_since: HashMap::new(),
+            samples: HashMap::new(),
+            last_sample: None,
+            anomalies: Vec::new(),
+        }
+    }
+
+    /// Change the `D` state threshold from the next update on.
+    pub fn set_d_state_secs(&mut self, secs: u64) {
+        self.d_state_secs = secs;
+    }
+
+    /// Fold in the latest process scan taken at `now` (seconds since the epoch).
+    pub fn update(&mut self, processes: &[ProcessInfo], now: u64) {
+        let blocked_since = processes
+            .iter()
+            .filter(|p| p.state == 'D')
+            .map(|p| (p.pid, *self.blocked_since.get(&p.pid).unwrap_or(&now)))
+            .collect();
+        self.blocked_since = blocked_since;
+
+        if self
+            .last_sample
+            .is_none_or(|at| now.saturating_sub(at) >= SAMPLE_INTERVAL_SECS)
+        {
+            let mut samples = HashMap::with_capacity(processes.len());
+            for process in processes {
+                let mut history = self.samples.remove(&process.pid).unwrap_or_default();
+                if history.len() == GROWTH_WINDOW {
+                    history.pop_front();
+                }
+                history.push_back(Sample {
+                    rss: process.rss.0,
+                    fds: count_fds(process.pid),
+                });
+                samples.insert(process.pid, history);
+            }
+            self.samples = samples;
+            self.last_sample = Some(now);
+        }
+
+        self.anomalies.clear();
+        for process in processes {
+            let mut kinds = Vec::new();
+            match process.state {
+                'Z' => kinds.push(AnomalyKind::Zombie { ppid: process.ppid }),
+                'D' => {
+                    let secs = now.saturating_sub(self.blocked_since[&process.pid]);
+                    if secs > self.d_state_secs {
+                        kinds.push(AnomalyKind::Blocked { secs });
+                    }
+                }
+                _ => {}
+            }
+            if let Some(history) = self
+                .samples
+                .get(&process.pid)
+                .filter(|h| h.len() == GROWTH_WINDOW)
+            {
+                if let Some((from, to)) =
+                    steady_growth(history.iter().map(|s| Some(s.rss)), MIN_RSS_GROWTH)
+                {
+                    kinds.push(AnomalyKind::RssGrowth {
+                        from: Bytes(from),
+                        to: Bytes(to),
+                    });
+                }
+                if let Some((from, to)) =
+                    steady_growth(history.iter().map(|s| s.fds), MIN_FD_GROWTH)
+                {
+                    kinds.push(AnomalyKind::FdGrowth { from, to });
+                }
+            }
+            if kinds.is_empty() {
+                continue;
+            }
+            let wchan = read_wchan(process.pid);
+            self.anomalies.extend(kinds.into_iter().map(|kind| Anomaly {
+                pid: process.pid,
+                name: process.name.clone(),
+                kind,
+                wchan: wchan.clone(),
+            }));
+        }
+        self.anomalies.sort_by_key(|a| a.pid);
+    }
+
+    /// Processes flagged by the latest update, ordered by pid.
+    pub fn anomalies(&self) -> &[Anomaly] {
+        &self.anomalies
+    }
+}
+
+/// First and last value when the series never decreases and grows by at least
+/// `min_growth`. Any missing value disqualifies the series.
+pub fn steady_growth<I>(values: I, min_growth: u64) -> Option<(u64, u64)>
+where
+    I: IntoIterator<Item = Option<u64>>,
+{
+    let mut values = values.into_iter();
+    let first = values.next()??;
+    let mut last = first;
+    for value in values {
+        let value = value?;
+        if value < last {
+            return None;
+        }
+        last = value;
+    }
+    (last.saturating_sub(first) >= min_growth).then_some((first, last))
+}
+
+/// Number of open file descriptors, `None` when not permitted.
+fn count_fds(pid: u32) -> Option<u64> {
+    fs::read_dir(format!("/proc/{pid}/fd"))
+        .ok()
+        .map(|entries| entries.count() as u64)
+}
+
+/// The wait channel, `None` when unreadable or the process is running.
+fn read_wchan(pid: u32) -> Option<String> {
+    fs::read_to_string(format!("/proc/{pid}/wchan"))
+        .ok()
+        .map(|text| text.trim().to_string())
+        .filter(|wchan| !wchan.is_empty() && wchan != "0")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Above the kernel's pid limit, so nothing under /proc can match.
+    const FAKE_PID: u32 = 5_000_000;
+
+    fn process(state: char, rss: u64) -> ProcessInfo {
+        ProcessInfo {
+            ppid: 7,
+            name: "svc".into(),
+            state,
+            rss: Bytes(rss),
+            ..ProcessInfo::test(FAKE_PID)
+        }
+    }
+
+    #[test]
+    fn steady_growth_requires_monotonic_series_and_minimum() {
+        let series = |values: &[u64]| values.iter().map(|&v| Some(v)).collect::<Vec<_>>();
+        assert_eq!(steady_growth(series(&[1, 5, 5, 30]), 20), Some((1, 30)));
+        assert_eq!(steady_growth(series(&[1, 5, 4, 30]), 20), None);
+        assert_eq!(steady_growth(series(&[1, 5, 5, 10]), 20), None);
+        assert_eq!(steady_growth([Some(1), None, Some(30)], 20), None);
+        assert_eq!(steady_growth([], 0), None);
+    }
+
+    #[test]
+    fn flags_zombies_and_long_d_state() {
+        let mut detector = AnomalyDetector::new(D_STATE_SECS);
+        detector.update(&[process('Z', 0)], 100);
+        assert_eq!(
+            detector.anomalies()[0].kind,
+            AnomalyKind::Zombie { ppid: 7 }
+        );
+
+        detector.update(&[process('D', 0)], 200);
+        assert!(detector.anomalies().is_empty());
+        detector.update(&[process('D', 0)], 200 + D_STATE_SECS + 1);
+        assert_eq!(
+            detector.anomalies()[0].kind,
+            AnomalyKind::Blocked {
+                secs: D_STATE_SECS + 1
+            }
+        );
+        // Leaving D state resets the timer.
+        detector.update(&[process('S', 0)], 300);
+        detector.update(&[process('D', 0)], 301);
+        assert!(detector.anomalies().is_empty());
+    }
+
+    #[test]
+    fn d_state_threshold_is_configurable() {
+        let mut detector = AnomalyDetector::new(3);
+        detector.update(&[process('D', 0)], 100);
+        detector.update(&[process('D', 0)], 103);
+        assert!(detector.anomalies().is_empty());
+        detector.update(&[process('D', 0)], 104);
+        assert_eq!(
+            detector.anomalies()[0].kind,
+            AnomalyKind::Blocked { secs: 4 }
+        );
+
+        // Raising the threshold applies to processes already being timed.
+        detector.set_d_state_secs(5);
+        detector.update(&[process('D', 0)], 105);
+        assert!(detector.anomalies().is_empty());
+        detector.update(&[process('D', 0)], 106);
+        assert_eq!(
+            detector.anomalies()[0].kind,
+            AnomalyKind::Blocked { secs: 6 }
+        );
+    }
+
+    #[test]
+    fn flags_rss_growing_across_the_whole_window() {
+        let mut detector = AnomalyDetector::new(D_STATE_SECS);
+        let step = MIN_RSS_GROWTH;
+        for i in 0..GROWTH_WINDOW as u64 {
+            assert!(detector.anomalies().is_empty());
+            detector.update(&[process('S', i * step)], i * SAMPLE_INTERVAL_SECS);
+        }
+        let last = (GROWTH_WINDOW as u64 - 1) * step;
+        assert_eq!(
+            detector.anomalies()[0].kind,
+            AnomalyKind::RssGrowth {
+                from: Bytes(0),
+                to: Bytes(last)
+            }
+        );
+
+        // A single drop clears the flag until the window is steady again.
+        detector.update(
+            &[process('S', 0)],
+            GROWTH_WINDOW as u64 * SAMPLE_INTERVAL_SECS,
+        );
+        assert!(detector.anomalies().is_empty());
+    }
+}
@@ -0,0 +1,263 @@
+//! Process exec/exit events from the kernel's netlink proc connector.
+//!
+//! Subscribing needs `CAP_NET_ADMIN`; without it [`ProcConnector::open`]
+//! returns `None` and rustop relies on polling `/proc` alone.
+
+use std::{
+    fs, mem,
+    os::fd::{AsRawFd, FromRawFd, OwnedFd},
+    sync::mpsc::{self, Receiver},
+    thread,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::process::split_nul;
+
+const CN_IDX_PROC: u32 = 1;
+const CN_VAL_PROC: u32 = 1;
+const PROC_CN_MCAST_LISTEN: u32 = 1;
+const PROC_EVENT_EXEC: u32 = 0x0000_0002;
+const PROC_EVENT_EXIT: u32 = 0x8000_0000;
+
+const NLMSG_HEADER_LEN: usize = 16;
+const CN_MSG_HEADER_LEN: usize = 20;
+/// Offset of `event_data` inside `struct proc_event` (what, cpu, timestamp_ns).
+const EVENT_DATA_OFFSET: usize = 16;
+
+/// A process event as decoded from one netlink message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RawEvent {
+    Exec { pid: u32, tgid: u32 },
+    Exit { pid: u32, tgid: u32 },
+}
+
+/// A process-level event with the details captured when it arrived.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConnectorEvent {
+    /// A process replaced its image; name and command are read immediately.
+    Exec {
+        pid: u32,
+        name: String,
+        command: String,
+        time: u64,
+    },
+    Exit {
+        pid: u32,
+        time: u64,
+    },
+}
+
+/// Receives proc connector events on a background thread.
+pub struct ProcConnector {
+    events: Receiver<ConnectorEvent>,
+}
+
+impl ProcConnector {
+    /// Subscribe to process events, or `None` when not permitted or unsupported.
+    pub fn open() -> Option<Self> {
+        let socket = subscribe().ok()?;
+        let (sender, events) = mpsc::channel();
+        thread::Builder::new()
+            .name("proc-connector".into())
+            .spawn(move || {
+                let mut buf = [0u8; 1024];
+                loop {
+                    // SAFETY: `buf` is valid for writes of its full length.
+                    let len = unsafe {
+                        libc::recv(socket.as_raw_fd(), buf.as_mut_ptr().cast(), buf.len(), 0)
+                    };
+                    if len < 0 {
+                        if std::io::Error::last_os_error().kind() == std::io::ErrorKind::Interrupted
+                        {
+                            continue;
+                        }
+                        return;
+                    }
+                    let Some(event) = parse_message(&buf[..len as usize]).and_then(capture) else {
+                        continue;
+                    };
+                    if sender.send(event).is_err() {
+                        return;
+                    }
+                }
+            })
+            .ok()?;
+        Some(Self { events })
+    }
+
+    /// Events received since the previous call.
+    pub fn drain(&self) -> Vec<ConnectorEvent> {
+        self.events.try_iter().collect()
+    }
+}
+
+/// Decode a proc connector netlink message, keeping whole-process exec/exit events.
+pub fn parse_message(buf: &[u8]) -> Option<RawEvent> {
+    let u32_at = |offset: usize| {
+        buf.get(offset..offset + 4)
+            .map(|bytes| u32::from_ne_bytes(bytes.try_into().unwrap()))
+    };
+    if u32_at(NLMSG_HEADER_LEN)? != CN_IDX_PROC || u32_at(NLMSG_HEADER_LEN + 4)? != CN_VAL_PROC {
+        return None;
+    }
+    let event = NLMSG_HEADER_LEN + CN_MSG_HEADER_LEN;
+    let data = event + EVENT_DATA_OFFSET;
+    let pid = u32_at(data)?;
+    let tgid = u32_at(data + 4)?;
+    match u32_at(event)? {
+        PROC_EVENT_EXEC => Some(RawEvent::Exec { pid, tgid }),
+        PROC_EVENT_EXIT => Some(RawEvent::Exit { pid, tgid }),
+        _ => None,
+    }
+}
+
+/// The `PROC_CN_MCAST_LISTEN` request sent after binding.
+pub fn subscribe_message(port: u32) -> Vec<u8> {
+    let total = (NLMSG_HEADER_LEN + CN_MSG_HEADER_LEN + 4) as u32;
+    let mut msg = Vec::with_capacity(total as usize);
+    // nlmsghdr: len, type, flags, seq, pid
+    msg.extend_from_slice(&total.to_ne_bytes());
+    msg.extend_from_slice(&(libc::NLMSG_DONE as u16).to_ne_bytes());
+    msg.extend_from_slice(&0u16.to_ne_bytes());
+    msg.extend_from_slice(&0u32.to_ne_bytes());
+    msg.extend_from_slice(&port.to_ne_bytes());
+    // cn_msg: id.idx, id.val, seq, ack, len, flags
+    msg.extend_from_slice(&CN_IDX_PROC.to_ne_bytes());
+    msg.extend_from_slice(&CN_VAL_PROC.to_ne_bytes());
+    msg.extend_from_slice(&0u32.to_ne_bytes());
+    msg.extend_from_slice(&0u32.to_ne_bytes());
+    msg.extend_from_slice(&4u16.to_ne_bytes());
+    msg.extend_from_slice(&0u16.to_ne_bytes());
+    msg.extend_from_slice(&PROC_CN_MCAST_LISTEN.to_ne_bytes());
+    msg
+}
+
+fn subscribe() -> std::io::Result<OwnedFd> {
+    // SAFETY: plain socket(2) call; the descriptor is owned immediately.
+    let fd = unsafe {
+        libc::socket(
+            libc::AF_NETLINK,
+            libc::SOCK_DGRAM | libc::SOCK_CLOEXEC,
+            libc::NETLINK_CONNECTOR,
+        )
+    };
+    if fd < 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    // SAFETY: `fd` is a freshly created descriptor nobody else owns.
+    let socket = unsafe { OwnedFd::from_raw_fd(fd) };
+
+    // SAFETY: sockaddr_nl is plain data; zeroed is a valid initial value and
+    // the pointer/length pair passed to bind describes it exactly.
+    let bound = unsafe {
+        let mut addr: libc::sockaddr_nl = mem::zeroed();
+        addr.nl_family = libc::AF_NETLINK as libc::sa_family_t;
+        // Zero lets the kernel pick a unique port for this socket.
+        addr.nl_pid = 0;
+        addr.nl_groups = CN_IDX_PROC;
+        libc::bind(
+            socket.as_raw_fd(),
+            (&addr as *const libc::sockaddr_nl).cast(),
+            mem::size_of::<libc::sockaddr_nl>() as libc::socklen_t,
+        )
+    };
+    if bound != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+
+    // The request's sender port must match the one the kernel assigned, which
+    // is only the process id for the first netlink socket of the process.
+    // SAFETY: as above; `len` holds the size of `addr` on entry.
+    let port = unsafe {
+        let mut addr: libc::sockaddr_nl = mem::zeroed();
+        let mut len = mem::size_of::<libc::sockaddr_nl>() as libc::socklen_t;
+        if libc::getsockname(
+            socket.as_raw_fd(),
+            (&mut addr as *mut libc::sockaddr_nl).cast(),
+            &mut len,
+        ) != 0
+        {
+            return Err(std::io::Error::last_os_error());
+        }
+        addr.nl_pid
+    };
+
+    let msg = subscribe_message(port);
+    // SAFETY: `msg` is valid for reads of its full length.
+    let sent = unsafe { libc::send(socket.as_raw_fd(), msg.as_ptr().cast(), msg.len(), 0) };
+    if sent < 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(socket)
+}
+
+/// Turn a raw event into a process-level event, reading names while the
+/// process still exists. Thread exec/exit events are ignored.
+fn capture(event: RawEvent) -> Option<ConnectorEvent> {
+    let time = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    match event {
+        RawEvent::Exec { pid, tgid } if pid == tgid => {
+            let name = fs::read_to_string(format!("/proc/{pid}/comm"))
+                .map(|name| name.trim().to_string())
+                .unwrap_or_else(|_| "?".to_string());
+            let command = fs::read(format!("/proc/{pid}/cmdline"))
+                .map(|raw| split_nul(&raw).join(" "))
+                .unwrap_or_default();
+            Some(ConnectorEvent::Exec {
+                pid,
+                name,
+                command,
+                time,
+            })
+        }
+        RawEvent::Exit { pid, tgid } if pid == tgid => Some(ConnectorEvent::Exit { pid, time }),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(what: u32, pid: u32, tgid: u32) -> Vec<u8> {
+        let mut buf = vec![0u8; NLMSG_HEADER_LEN];
+        buf.extend_from_slice(&CN_IDX_PROC.to_ne_bytes());
+        buf.extend_from_slice(&CN_VAL_PROC.to_ne_bytes());
+        buf.extend_from_slice(&[0u8; 12]);
+        buf.extend_from_slice(&what.to_ne_bytes());
+        buf.extend_from_slice(&[0u8; 12]);
+        buf.extend_from_slice(&pid.to_ne_bytes());
+        buf.extend_from_slice(&tgid.to_ne_bytes());
+        buf
+    }
+
+    #[test]
+    fn parse_message_decodes_exec_and_exit() {
+        assert_eq!(
+            parse_message(&message(PROC_EVENT_EXEC, 42, 42)),
+            Some(RawEvent::Exec { pid: 42, tgid: 42 })
+        );
+        assert_eq!(
+            parse_message(&message(PROC_EVENT_EXIT, 43, 42)),
+            Some(RawEvent::Exit { pid: 43, tgid: 42 })
+        );
+        // Fork events and truncated buffers are ignored.
+        assert_eq!(parse_message(&message(1, 42, 42)), None);
+        assert_eq!(parse_message(&message(PROC_EVENT_EXEC, 42, 42)[..30]), None);
+    }
+
+    #[test]
+    fn subscribe_message_layout() {
+        let msg = subscribe_message(7);
+        assert_eq!(msg.len(), 40);
+        assert_eq!(u32::from_ne_bytes(msg[0..4].try_into().unwrap()), 40);
+        assert_eq!(u32::from_ne_bytes(msg[12..16].try_into().unwrap()), 7);
+        assert_eq!(
+            u32::from_ne_bytes(msg[36..40].try_into().unwrap()),
+            PROC_CN_MCAST_LISTEN
+        );
+    }
+}
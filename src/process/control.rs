@@ -0,0 +1,475 @@
+//! Scheduling controls for a process: nice value, I/O priority and CPU affinity.
+
+use std::{fs, io, mem, path::Path};
+
+use crate::process::{list_pids, parse_stat};
+
+const IOPRIO_CLASS_SHIFT: i32 = 13;
+const IOPRIO_WHO_PROCESS: libc::c_int = 1;
+
+/// I/O scheduling class as used by `ioprio_set(2)`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum IoClass {
+    /// No explicit class; the kernel derives best-effort priority from nice.
+    #[default]
+    None,
+    RealTime,
+    BestEffort,
+    Idle,
+}
+
+impl IoClass {
+    /// The class after this one, wrapping around.
+    pub fn next(self) -> Self {
+        match self {
+            IoClass::None => IoClass::BestEffort,
+            IoClass::BestEffort => IoClass::Idle,
+            IoClass::Idle => IoClass::RealTime,
+            IoClass::RealTime => IoClass::None,
+        }
+    }
+
+    /// The class before this one, wrapping around.
+    pub fn previous(self) -> Self {
+        match self {
+            IoClass::None => IoClass::RealTime,
+            IoClass::BestEffort => IoClass::None,
+            IoClass::Idle => IoClass::BestEffort,
+            IoClass::RealTime => IoClass::Idle,
+        }
+    }
+
+    /// Name as printed by `ionice`.
+    pub fn label(self) -> &'static str {
+        match self {
+            IoClass::None => "none",
+            IoClass::RealTime => "realtime",
+            IoClass::BestEffort => "best-effort",
+            IoClass::Idle => "idle",
+        }
+    }
+}
+
+/// An I/O priority: class plus level 0 (highest) to 7 (lowest).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct IoPriority {
+    pub class: IoClass,
+    pub level: u8,
+}
+
+impl IoPriority {
+    /// Decode the value returned by `ioprio_get(2)`.
+    pub fn from_raw(raw: i32) -> Self {
+        let class = match raw >> IOPRIO_CLASS_SHIFT {
+            1 => IoClass::RealTime,
+            2 => IoClass::BestEffort,
+            3 => IoClass::Idle,
+            _ => IoClass::None,
+        };
+        Self {
+            class,
+            level: (raw & ((1 << IOPRIO_CLASS_SHIFT) - 1)).clamp(0, 7) as u8,
+        }
+    }
+
+    /// Encode for `ioprio_set(2)`.
+    pub fn to_raw(self) -> i32 {
+        let class = match self.class {
+            IoClass::None => 0,
+            IoClass::RealTime => 1,
+            IoClass::BestEffort => 2,
+            IoClass::Idle => 3,
+        };
+        let level = match self.class {
+            IoClass::None | IoClass::Idle => 0,
+            _ => i32::from(self.level.min(7)),
+        };
+        (class << IOPRIO_CLASS_SHIFT) | level
+    }
+}
+
+/// Field focused in the scheduling editor.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EditField {
+    #[default]
+    Nice,
+    IoClass,
+    IoLevel,
+    Affinity,
+}
+
+impl EditField {
+    fn next(self) -> Self {
+        match self {
+            EditField::Nice => EditField::IoClass,
+            EditField::IoClass => EditField::IoLevel,
+            EditField::IoLevel => EditField::Affinity,
+            EditField::Affinity => EditField::Affinity,
+        }
+    }
+
+    fn previous(self) -> Self {
+        match self {
+            EditField::Nice => EditField::Nice,
+            EditField::IoClass => EditField::Nice,
+            EditField::IoLevel => EditField::IoClass,
+            EditField::Affinity => EditField::IoLevel,
+        }
+    }
+}
+
+/// Pending nice, I/O priority and affinity changes for one process.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchedulingEdit {
+    pub pid: u32,
+    pub name: String,
+    pub nice: i32,
+    pub io: IoPriority,
+    /// CPU ids shown in the affinity grid.
+    pub cpus: Vec<usize>,
+    /// One entry per id in `cpus`; `true` when the process may run there.
+    pub affinity: Vec<bool>,
+    pub field: EditField,
+    /// CPU under the cursor in the affinity grid.
+    pub cursor: usize,
+    /// Outcome of the last apply: `Ok` summary or `Err` explanation.
+    pub message: Option<Result<String, String>>,
+    applied: (i32, IoPriority, Vec<bool>),
+}
+
+impl SchedulingEdit {
+    /// Read the current settings of `pid`, with affinity for the given CPU ids.
+    pub fn load(pid: u32, cpus: &[usize]) -> Result<Self, String> {
+        let stat = fs::read_to_string(format!("/proc/{pid}/stat"))
+            .ok()
+            .and_then(|text| parse_stat(&text))
+            .ok_or_else(|| format!("process {pid} has exited"))?;
+        let nice = stat.nice as i32;
+        let io = io_priority(pid).map_err(|err| describe_error("read I/O priority", &err))?;
+        let affinity = affinity(pid, cpus).map_err(|err| describe_error("read affinity", &err))?;
+        Ok(Self {
+            pid,
+            name: stat.comm,
+            nice,
+            io,
+            cpus: cpus.to_vec(),
+            affinity: affinity.clone(),
+            field: EditField::default(),
+            cursor: 0,
+            message: None,
+            applied: (nice, io, affinity),
+        })
+    }
+
+    /// Focus the next field.
+    pub fn next_field(&mut self) {
+        self.field = self.field.next();
+    }
+
+    /// Focus the previous field.
+    pub fn previous_field(&mut self) {
+        self.field = self.field.previous();
+    }
+
+    /// Left/Right on the focused field: change the value or move the grid cursor.
+    pub fn adjust(&mut self, delta: i32) {
+        match self.field {
+            EditField::Nice => self.nice = (self.nice + delta).clamp(-20, 19),
+            EditField::IoClass => {
+                for _ in 0..delta.unsigned_abs() {
+                    self.io.class = if delta > 0 {
+                        self.io.class.next()
+                    } else {
+                        self.io.class.previous()
+                    };
+                }
+            }
+            EditField::IoLevel => {
+                self.io.level = (i32::from(self.io.level) + delta).clamp(0, 7) as u8
+            }
+            EditField::Affinity => {
+                let last = self.affinity.len().saturating_sub(1) as i32;
+                self.cursor = (self.cursor as i32 + delta).clamp(0, last) as usize;
+            }
+        }
+    }
+
+    /// Toggle the CPU under the cursor, refusing to clear the last one.
+    pub fn toggle_cpu(&mut self) {
+        let selected = self.affinity.iter().filter(|on| **on).count();
+        if let Some(on) = self.affinity.get_mut(self.cursor) {
+            if !*on || selected > 1 {
+                *on = !*on;
+            }
+        }
+    }
+
+    /// Allow every CPU.
+    pub fn select_all_cpus(&mut self) {
+        self.affinity.iter_mut().for_each(|on| *on = true);
+    }
+
+    /// Apply whatever changed since loading or the last successful apply.
+    pub fn apply(&mut self) {
+        let mut done = Vec::new();
+        let mut errors = Vec::new();
+
+        if self.nice != self.applied.0 {
+            match set_nice(self.pid, self.nice) {
+                Ok(()) => {
+                    self.applied.0 = self.nice;
+                    done.push(format!("nice {}", self.nice));
+                }
+                Err(err) => errors.push(describe_error("renice", &err)),
+            }
+        }
+        if self.io != self.applied.1 {
+            match set_io_priority(self.pid, self.io) {
+                Ok(()) => {
+                    self.applied.1 = self.io;
+                    done.push(format!("I/O {} {}", self.io.class.label(), self.io.level));
+                }
+                Err(err) => errors.push(describe_io_error(self.io.class, &err)),
+            }
+        }
+        if self.affinity != self.applied.2 {
+            let allowed: Vec<usize> = self
+                .cpus
+                .iter()
+                .zip(&self.affinity)
+                .filter(|(_, on)| **on)
+                .map(|(cpu, _)| *cpu)
+                .collect();
+            match set_affinity(self.pid, &allowed) {
+                Ok(()) => {
+                    self.applied.2 = self.affinity.clone();
+                    done.push("affinity".to_string());
+                }
+                Err(err) => errors.push(describe_error("set affinity", &err)),
+            }
+        }
+
+        self.message = Some(if !errors.is_empty() {
+            Err(errors.join("; "))
+        } else if done.is_empty() {
+            Ok("Nothing to apply".to_string())
+        } else {
+            Ok(format!("Applied {}", done.join(", ")))
+        });
+    }
+}
+
+/// Current I/O priority of `pid`.
+pub fn io_priority(pid: u32) -> io::Result<IoPriority> {
+    // SAFETY: ioprio_get takes two integers and touches no memory.
+    let raw =
+        unsafe { libc::syscall(libc::SYS_ioprio_get, IOPRIO_WHO_PROCESS, pid as libc::c_int) };
+    if raw < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(IoPriority::from_raw(raw as i32))
+}
+
+/// Whether `pid` may run on each of the CPU ids in `cpus`.
+pub fn affinity(pid: u32, cpus: &[usize]) -> io::Result<Vec<bool>> {
+    // SAFETY: `set` is a properly sized, zeroed cpu_set_t owned by this frame.
+    unsafe {
+        let mut set: libc::cpu_set_t = mem::zeroed();
+        if libc::sched_getaffinity(
+            pid as libc::pid_t,
+            mem::size_of::<libc::cpu_set_t>(),
+            &mut set,
+        ) != 0
+        {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(cpus
+            .iter()
+            .map(|&cpu| cpu < libc::CPU_SETSIZE as usize && libc::CPU_ISSET(cpu, &set))
+            .collect())
+    }
+}
+
+/// Set the nice value of every thread of `pid`.
+pub fn set_nice(pid: u32, nice: i32) -> io::Result<()> {
+    for_each_thread(pid, |tid| {
+        // SAFETY: setpriority takes plain integers.
+        let result = unsafe { libc::setpriority(libc::PRIO_PROCESS, tid as libc::id_t, nice) };
+        if result != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    })
+}
+
+/// Set the I/O priority of every thread of `pid`.
+pub fn set_io_priority(pid: u32, priority: IoPriority) -> io::Result<()> {
+    for_each_thread(pid, |tid| {
+        // SAFETY: ioprio_set takes three integers and touches no memory.
+        let result = unsafe {
+            libc::syscall(
+                libc::SYS_ioprio_set,
+                IOPRIO_WHO_PROCESS,
+                tid as libc::c_int,
+                priority.to_raw(),
+            )
+        };
+        if result != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    })
+}
+
+/// Restrict every thread of `pid` to the CPU ids in `cpus`.
+pub fn set_affinity(pid: u32, cpus: &[usize]) -> io::Result<()> {
+    // SAFETY: `set` is a zeroed cpu_set_t; CPU_SET is bounds-checked by the
+    // filter against CPU_SETSIZE.
+    let set = unsafe {
+        let mut set: libc::cpu_set_t = mem::zeroed();
+        for &cpu in cpus.iter().filter(|&&cpu| cpu < libc::CPU_SETSIZE as usize) {
+            libc::CPU_SET(cpu, &mut set);
+        }
+        set
+    };
+    for_each_thread(pid, |tid| {
+        // SAFETY: `set` outlives the call and its size is passed alongside.
+        let result = unsafe {
+            libc::sched_setaffinity(tid as libc::pid_t, mem::size_of::<libc::cpu_set_t>(), &set)
+        };
+        if result != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    })
+}
+
+/// Explain a failed control call in terms of what the user can do about it.
+pub fn describe_error(action: &str, err: &io::Error) -> String {
+    let reason = match err.raw_os_error() {
+        Some(libc::EPERM) | Some(libc::EACCES) => {
+            "permission denied (raising priority or changing another user's process needs root or CAP_SYS_NICE)"
+                .to_string()
+        }
+        Some(libc::ESRCH) => "process has exited".to_string(),
+        Some(libc::EINVAL) => "invalid value for this process or system".to_string(),
+        _ => err.to_string(),
+    };
+    format!("{action} failed: {reason}")
+}
+
+/// [`describe_error`] for `ioprio_set(2)`, which checks `CAP_SYS_ADMIN`
+/// rather than `CAP_SYS_NICE` for the realtime class.
+fn describe_io_error(class: IoClass, err: &io::Error) -> String {
+    match err.raw_os_error() {
+        Some(libc::EPERM) | Some(libc::EACCES) if class == IoClass::RealTime => {
+            "set I/O priority failed: permission denied (the realtime class needs root or CAP_SYS_ADMIN)"
+                .to_string()
+        }
+        _ => describe_error("set I/O priority", err),
+    }
+}
+
+/// Run `apply` for every thread of `pid`; the scheduler settings are per
+/// thread, so changing only the main thread would miss worker threads.
+fn for_each_thread(pid: u32, mut apply: impl FnMut(u32) -> io::Result<()>) -> io::Result<()> {
+    let mut threads = list_pids(Path::new(&format!("/proc/{pid}/task")));
+    if threads.is_empty() {
+        threads.push(pid);
+    }
+    threads.sort_unstable();
+    for tid in threads {
+        match apply(tid) {
+            // Threads may exit between listing and applying.
+            Err(err) if err.raw_os_error() == Some(libc::ESRCH) && tid != pid => {}
+            result => result?,
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::topology;
+
+    #[test]
+    fn io_priority_round_trips() {
+        let priority = IoPriority {
+            class: IoClass::BestEffort,
+            level: 4,
+        };
+        assert_eq!(priority.to_raw(), (2 << 13) | 4);
+        assert_eq!(IoPriority::from_raw(priority.to_raw()), priority);
+        assert_eq!(
+            IoPriority::from_raw(3 << 13),
+            IoPriority {
+                class: IoClass::Idle,
+                level: 0,
+            }
+        );
+        assert_eq!(IoPriority::from_raw(0).class, IoClass::None);
+    }
+
+    #[test]
+    fn edit_clamps_values_and_keeps_one_cpu() {
+        let mut edit = SchedulingEdit::load(std::process::id(), &[0, 2]).unwrap();
+        edit.affinity = vec![true, false];
+        edit.nice = 18;
+        edit.adjust(5);
+        assert_eq!(edit.nice, 19);
+
+        edit.next_field();
+        edit.io.class = IoClass::BestEffort;
+        edit.adjust(-1);
+        assert_eq!(edit.io.class, IoClass::None);
+        edit.adjust(-1);
+        assert_eq!(edit.io.class, IoClass::RealTime);
+        edit.adjust(1);
+        assert_eq!(edit.io.class, IoClass::None);
+
+        edit.next_field();
+        edit.adjust(-10);
+        assert_eq!(edit.io.level, 0);
+
+        edit.next_field();
+        assert_eq!(edit.field, EditField::Affinity);
+        edit.toggle_cpu();
+        assert_eq!(edit.affinity, vec![true, false]);
+        edit.adjust(1);
+        edit.toggle_cpu();
+        assert_eq!(edit.affinity, vec![true, true]);
+        edit.adjust(1);
+        assert_eq!(edit.cursor, 1);
+    }
+
+    #[test]
+    fn describe_error_explains_permissions() {
+        let err = io::Error::from_raw_os_error(libc::EPERM);
+        assert!(describe_error("renice", &err).contains("CAP_SYS_NICE"));
+        let err = io::Error::from_raw_os_error(libc::ESRCH);
+        assert_eq!(
+            describe_error("renice", &err),
+            "renice failed: process has exited"
+        );
+        let err = io::Error::from_raw_os_error(libc::EPERM);
+        assert!(describe_io_error(IoClass::RealTime, &err).contains("CAP_SYS_ADMIN"));
+        assert!(describe_io_error(IoClass::BestEffort, &err).contains("CAP_SYS_NICE"));
+    }
+
+    #[test]
+    fn affinity_matches_cpus_allowed_list() {
+        let status = fs::read_to_string("/proc/self/status").unwrap();
+        let allowed = status
+            .lines()
+            .find_map(|line| line.strip_prefix("Cpus_allowed_list:"))
+            .map(topology::parse_cpu_list)
+            .unwrap();
+        let cpus: Vec<usize> = (0..=allowed.iter().max().unwrap() + 1).collect();
+
+        let expected: Vec<bool> = cpus.iter().map(|cpu| allowed.contains(cpu)).collect();
+        assert_eq!(affinity(std::process::id(), &cpus).unwrap(), expected);
+        // Ids beyond the kernel's cpu_set_t are never allowed.
+        assert_eq!(affinity(std::process::id(), &[1 << 20]).unwrap(), [false]);
+    }
+}
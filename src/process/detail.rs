@@ -0,0 +1,328 @@
+//! Detailed view of a single process assembled from `/proc/<pid>`.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    cgroup,
+    process::{parse_stat, split_nul, ProcClock, ProcStat},
+    units::Bytes,
+};
+
+/// Fields from `/proc/<pid>/status` shown in the detail pane, in display order.
+const STATUS_FIELDS: [&str; 12] = [
+    "State",
+    "PPid",
+    "Uid",
+    "Gid",
+    "Threads",
+    "VmPeak",
+    "VmSize",
+    "VmRSS",
+    "VmSwap",
+    "Cpus_allowed_list",
+    "voluntary_ctxt_switches",
+    "nonvoluntary_ctxt_switches",
+];
+
+/// Memory totals from `/proc/<pid>/smaps_rollup`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SmapsRollup {
+    pub rss: Bytes,
+    pub pss: Bytes,
+    /// Unique set size: private clean plus private dirty pages.
+    pub uss: Bytes,
+    pub swap: Bytes,
+    pub swap_pss: Bytes,
+}
+
+/// One row of `/proc/<pid>/limits`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Limit {
+    pub name: String,
+    pub soft: String,
+    pub hard: String,
+    pub units: String,
+}
+
+/// Everything the detail pane shows about one process.
+#[derive(Debug, Clone)]
+pub struct ProcessDetail {
+    pub pid: u32,
+    pub stat: ProcStat,
+    pub cmdline: Vec<String>,
+    pub cwd: Option<PathBuf>,
+    pub exe: Option<PathBuf>,
+    /// `None` when the environment is not readable (another user's process).
+    pub environ: Option<Vec<String>>,
+    pub status: Vec<(String, String)>,
+    pub memory: Option<SmapsRollup>,
+    /// Open descriptors and their targets, `None` when not permitted.
+    pub fds: Option<Vec<(u32, String)>>,
+    pub threads: Vec<(u32, String)>,
+    pub limits: Vec<Limit>,
+    pub cgroup: Option<String>,
+    pub namespaces: Vec<(String, String)>,
+    /// Start time as seconds since the Unix epoch.
+    pub start_time: u64,
+}
+
+impl ProcessDetail {
+    /// Load the detail of `pid`, or `None` once the process has exited.
+    pub fn load(pid: u32, clock: ProcClock) -> Option<Self> {
+        Self::load_from(Path::new("/proc"), pid, clock)
+    }
+
+    /// Load from an arbitrary procfs root (used by tests with a fake tree).
+    pub fn load_from(proc_root: &Path, pid: u32, clock: ProcClock) -> Option<Self> {
+        let dir = proc_root.join(pid.to_string());
+        let stat = parse_stat(&fs::read_to_string(dir.join("stat")).ok()?)?;
+        let read = |name: &str| fs::read_to_string(dir.join(name)).ok();
+
+        Some(Self {
+            pid,
+            cmdline: fs::read(dir.join("cmdline"))
+                .map(|raw| split_nul(&raw))
+                .unwrap_or_default(),
+            cwd: fs::read_link(dir.join("cwd")).ok(),
+            exe: fs::read_link(dir.join("exe")).ok(),
+            environ: fs::read(dir.join("environ"))
+                .ok()
+                .map(|raw| split_nul(&raw)),
+            status: read("status")
+                .map(|text| parse_status_fields(&text))
+                .unwrap_or_default(),
+            memory: read("smaps_rollup").and_then(|text| parse_smaps_rollup(&text)),
+            fds: read_fds(&dir.join("fd")),
+            threads: read_threads(&dir.join("task")),
+            limits: read("limits")
+                .map(|text| parse_limits(&text))
+                .unwrap_or_default(),
+            cgroup: read("cgroup")
+                .and_then(|text| cgroup::parse_proc_cgroup(&text).map(str::to_string)),
+            namespaces: read_namespaces(&dir.join("ns")),
+            start_time: clock.start_epoch(stat.starttime),
+            stat,
+        })
+    }
+}
+
+/// Pick the fields listed in [`STATUS_FIELDS`] out of `/proc/<pid>/status`.
+pub fn parse_status_fields(text: &str) -> Vec<(String, String)> {
+    let fields: Vec<(&str, &str)> = text
+        .lines()
+        .filter_map(|line| line.split_once(':'))
+        .map(|(key, value)| (key.trim(), value.trim()))
+        .collect();
+    STATUS_FIELDS
+        .iter()
+        .filter_map(|wanted| {
+            fields
+                .iter()
+                .find(|(key, _)| key == wanted)
+                .map(|(key, value)| {
+                    (
+                        key.to_string(),
+                        value.split_whitespace().collect::<Vec<_>>().join(" "),
+                    )
+                })
+        })
+        .collect()
+}
+
+/// Parse `/proc/<pid>/smaps_rollup` (values in KiB).
+pub fn parse_smaps_rollup(text: &str) -> Option<SmapsRollup> {
+    let mut rollup = SmapsRollup::default();
+    let mut seen_rss = false;
+    for line in text.lines() {
+        let Some((key, rest)) = line.split_once(':') else {
+            continue;
+        };
+        let Some(kib) = rest.split_whitespace().next().and_then(|v| v.parse().ok()) else {
+            continue;
+        };
+        let bytes = Bytes::from_kib(kib);
+        match key {
+            "Rss" => {
+                rollup.rss = bytes;
+                seen_rss = true;
+            }
+            "Pss" => rollup.pss = bytes,
+            "Private_Clean" | "Private_Dirty" => rollup.uss += bytes,
+            "Swap" => rollup.swap = bytes,
+            "SwapPss" => rollup.swap_pss = bytes,
+            _ => {}
+        }
+    }
+    seen_rss.then_some(rollup)
+}
+
+/// Parse the fixed-width table in `/proc/<pid>/limits`.
+pub fn parse_limits(text: &str) -> Vec<Limit> {
+    let mut lines = text.lines();
+    let Some(header) = lines.next() else {
+        return Vec::new();
+    };
+    let (Some(soft_at), Some(hard_at), Some(units_at)) = (
+        header.find("Soft Limit"),
+        header.find("Hard Limit"),
+        header.find("Units"),
+    ) else {
+        return Vec::new();
+    };
+    let column = |line: &str, start: usize, end: usize| {
+        line.get(start..end.min(line.len()))
+            .unwrap_or("")
+            .trim()
+            .to_string()
+    };
+
+    lines
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| Limit {
+            name: column(line, 0, soft_at),
+            soft: column(line, soft_at, hard_at),
+            hard: column(line, hard_at, units_at),
+            units: column(line, units_at, line.len()),
+        })
+        .collect()
+}
+
+fn read_fds(fd_dir: &Path) -> Option<Vec<(u32, String)>> {
+    let mut fds: Vec<(u32, String)> = fs::read_dir(fd_dir)
+        .ok()?
+        .flatten()
+        .filter_map(|entry| {
+            let fd = entry.file_name().to_str()?.parse().ok()?;
+            let target = fs::read_link(entry.path())
+                .map(|t| t.display().to_string())
+                .unwrap_or_else(|_| "?".to_string());
+            Some((fd, target))
+        })
+        .collect();
+    fds.sort_by_key(|(fd, _)| *fd);
+    Some(fds)
+}
+
+fn read_threads(task_dir: &Path) -> Vec<(u32, String)> {
+    let Ok(entries) = fs::read_dir(task_dir) else {
+        return Vec::new();
+    };
+    let mut threads: Vec<(u32, String)> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let tid = entry.file_name().to_str()?.parse().ok()?;
+            let name = fs::read_to_string(entry.path().join("comm"))
+                .map(|n| n.trim().to_string())
+                .unwrap_or_default();
+            Some((tid, name))
+        })
+        .collect();
+    threads.sort_by_key(|(tid, _)| *tid);
+    threads
+}
+
+fn read_namespaces(ns_dir: &Path) -> Vec<(String, String)> {
+    let Ok(entries) = fs::read_dir(ns_dir) else {
+        return Vec::new();
+    };
+    let mut namespaces: Vec<(String, String)> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_str()?.to_string();
+            let target = fs::read_link(entry.path()).ok()?.display().to_string();
+            Some((name, target))
+        })
+        .collect();
+    namespaces.sort();
+    namespaces
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDir;
+
+    #[test]
+    fn parse_smaps_rollup_computes_uss() {
+        let text = "561331b7d000-7ffc95d8c000 ---p 00000000 00:00 0  [rollup]\n\
+                    Rss:                1296 kB\n\
+                    Pss:                 478 kB\n\
+                    Shared_Clean:       1116 kB\n\
+                    Private_Clean:        80 kB\n\
+                    Private_Dirty:       100 kB\n\
+                    Swap:                 12 kB\n\
+                    SwapPss:               6 kB\n";
+        let rollup = parse_smaps_rollup(text).unwrap();
+        assert_eq!(rollup.rss, Bytes::from_kib(1296));
+        assert_eq!(rollup.pss, Bytes::from_kib(478));
+        assert_eq!(rollup.uss, Bytes::from_kib(180));
+        assert_eq!(rollup.swap, Bytes::from_kib(12));
+        assert_eq!(rollup.swap_pss, Bytes::from_kib(6));
+        assert!(parse_smaps_rollup("").is_none());
+    }
+
+    #[test]
+    fn parse_limits_splits_columns() {
+        let text = "Limit                     Soft Limit           Hard Limit           Units     \n\
+                    Max cpu time              unlimited            unlimited            seconds   \n\
+                    Max open files            20000                20000                files     \n\
+                    Max nice priority         0                    0                    \n";
+        let limits = parse_limits(text);
+        assert_eq!(limits.len(), 3);
+        assert_eq!(
+            limits[1],
+            Limit {
+                name: "Max open files".into(),
+                soft: "20000".into(),
+                hard: "20000".into(),
+                units: "files".into(),
+            }
+        );
+        assert_eq!(limits[2].units, "");
+    }
+
+    #[test]
+    fn parse_status_fields_keeps_display_order() {
+        let text = "Name:\tcat\nThreads:\t1\nState:\tR (running)\nUid:\t0\t0\t0\t0\n";
+        assert_eq!(
+            parse_status_fields(text),
+            vec![
+                ("State".to_string(), "R (running)".to_string()),
+                ("Uid".to_string(), "0 0 0 0".to_string()),
+                ("Threads".to_string(), "1".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn load_from_reads_fake_proc_tree() {
+        let root = TempDir::new("detail");
+        root.write(
+            "42/stat",
+            "42 (worker) S 1 42 42 0 -1 0 0 0 0 0 3 4 0 0 20 0 1 0 500 0 10 0",
+        );
+        root.write("42/cmdline", b"worker\0--fast\0");
+        root.write("42/task/42/comm", "worker\n");
+        root.write("42/cgroup", "0::/system.slice/worker.service\n");
+
+        let clock = ProcClock {
+            ticks_per_sec: 100,
+            page_size: 4096,
+            boot_time: 1_000,
+        };
+        let detail = ProcessDetail::load_from(root.path(), 42, clock).unwrap();
+
+        assert_eq!(detail.cmdline, vec!["worker", "--fast"]);
+        assert_eq!(detail.threads, vec![(42, "worker".to_string())]);
+        assert_eq!(
+            detail.cgroup.as_deref(),
+            Some("/system.slice/worker.service")
+        );
+        assert_eq!(detail.start_time, 1_005);
+        assert!(detail.environ.is_none());
+        assert!(detail.memory.is_none());
+    }
+}
@@ -0,0 +1,312 @@
+//! Process start/exit detection by diffing successive process scans.
+
+use std::collections::{HashMap, VecDeque};
+
+use crate::{
+    process::{
+        connector::{ConnectorEvent, ProcConnector},
+        ProcessInfo,
+    },
+    units::Bytes,
+};
+
+/// Refreshes a started or exited process stays highlighted.
+pub const HIGHLIGHT_TICKS: u8 = 4;
+/// Events kept in the log.
+const EVENT_LOG_LEN: usize = 1000;
+/// Seconds an exec seen by the connector waits for its exit or a scan.
+const PENDING_EXEC_SECS: u64 = 10;
+
+/// What happened to a process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventKind {
+    Started,
+    Exited,
+    /// Started and exited between two scans; only seen via the proc connector.
+    ShortLived,
+}
+
+/// One entry in the lifecycle log.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProcessEvent {
+    /// Seconds since the Unix epoch.
+    pub time: u64,
+    pub kind: EventKind,
+    pub pid: u32,
+    pub name: String,
+    pub command: String,
+    /// Lifetime in seconds, known once the process has exited.
+    pub runtime_secs: Option<u64>,
+    /// Highest per-refresh CPU% and RSS observed, zero when never sampled.
+    pub peak_cpu: f32,
+    pub peak_rss: Bytes,
+}
+
+struct Tracked {
+    info: ProcessInfo,
+    peak_cpu: f32,
+    peak_rss: Bytes,
+}
+
+/// Diffs process scans into start/exit events and highlight state.
+pub struct LifecycleTracker {
+    initialized: bool,
+    tracked: HashMap<u32, Tracked>,
+    fresh: HashMap<u32, u8>,
+    exited: Vec<(ProcessInfo, u8)>,
+    events: VecDeque<ProcessEvent>,
+    connector: Option<ProcConnector>,
+    pending_exec: HashMap<u32, (String, String, u64)>,
+}
+
+impl LifecycleTracker {
+    /// Create a tracker, optionally fed by the proc connector.
+    pub fn new(connector: Option<ProcConnector>) -> Self {
+        Self {
+            initialized: false,
+            tracked: HashMap::new(),
+            fresh: HashMap::new(),
+            exited: Vec::new(),
+            events: VecDeque::new(),
+            connector,
+            pending_exec: HashMap::new(),
+        }
+    }
+
+    /// Compare `processes` with the previous scan. The first scan only
+    /// establishes the baseline.
+    pub fn update(&mut self, processes: &[ProcessInfo], now: u64) {
+        self.fresh.retain(|_, ticks| {
+            *ticks -= 1;
+            *ticks > 0
+        });
+        self.exited.retain_mut(|(_, ticks)| {
+            *ticks -= 1;
+            *ticks > 0
+        });
+
+        let mut seen = HashMap::with_capacity(processes.len());
+        for process in processes {
+            seen.insert(process.pid, ());
+            self.pending_exec.remove(&process.pid);
+            // A different start time means the pid was reused since the last
+            // scan: the old process exited and this is a new one.
+            if self
+                .tracked
+                .get(&process.pid)
+                .is_some_and(|tracked| tracked.info.start_time != process.start_time)
+            {
+                if let Some(tracked) = self.tracked.remove(&process.pid) {
+                    self.exit(tracked, now);
+                }
+            }
+            match self.tracked.get_mut(&process.pid) {
+                Some(tracked) => {
+                    tracked.peak_cpu = tracked.peak_cpu.max(process.cpu_percent);
+                    tracked.peak_rss = tracked.peak_rss.max(process.rss);
+                    tracked.info = process.clone();
+                }
+                None => {
+                    if self.initialized {
+                        self.fresh.insert(process.pid, HIGHLIGHT_TICKS);
+                        self.push(ProcessEvent {
+                            time: process.start_time,
+                            kind: EventKind::Started,
+                            pid: process.pid,
+                            name: process.name.clone(),
+                            command: process.command.clone(),
+                            runtime_secs: None,
+                            peak_cpu: process.cpu_percent,
+                            peak_rss: process.rss,
+                        });
+                    }
+                    self.tracked.insert(
+                        process.pid,
+                        Tracked {
+                            info: process.clone(),
+                            peak_cpu: process.cpu_percent,
+                            peak_rss: process.rss,
+                        },
+                    );
+                }
+            }
+        }
+
+        let gone: Vec<u32> = self
+            .tracked
+            .keys()
+            .filter(|pid| !seen.contains_key(pid))
+            .copied()
+            .collect();
+        for pid in gone {
+            if let Some(tracked) = self.tracked.remove(&pid) {
+                self.exit(tracked, now);
+            }
+        }
+
+        self.drain_connector(now);
+        self.initialized = true;
+    }
+
+    /// Log the exit of a process that is no longer in the scan.
+    fn exit(&mut self, tracked: Tracked, now: u64) {
+        self.push(ProcessEvent {
+            time: now,
+            kind: EventKind::Exited,
+            pid: tracked.info.pid,
+            name: tracked.info.name.clone(),
+            command: tracked.info.command.clone(),
+            runtime_secs: Some(now.saturating_sub(tracked.info.start_time)),
+            peak_cpu: tracked.peak_cpu,
+            peak_rss: tracked.peak_rss,
+        });
+        self.exited.push((tracked.info, HIGHLIGHT_TICKS));
+    }
+
+    /// Record processes the connector saw start and exit between two scans.
+    fn drain_connector(&mut self, now: u64) {
+        let Some(connector) = self.connector.as_ref() else {
+            return;
+        };
+        for event in connector.drain() {
+            match event {
+                ConnectorEvent::Exec {
+                    pid,
+                    name,
+                    command,
+                    time,
+                } => {
+                    if !self.tracked.contains_key(&pid) {
+                        self.pending_exec.insert(pid, (name, command, time));
+                    }
+                }
+                ConnectorEvent::Exit { pid, time } => {
+                    let Some((name, command, started)) = self.pending_exec.remove(&pid) else {
+                        continue;
+                    };
+                    if self.initialized {
+                        self.push(ProcessEvent {
+                            time,
+                            kind: EventKind::ShortLived,
+                            pid,
+                            name,
+                            command,
+                            runtime_secs: Some(time.saturating_sub(started)),
+                            peak_cpu: 0.0,
+                            peak_rss: Bytes(0),
+                        });
+                    }
+                }
+            }
+        }
+        self.pending_exec
+            .retain(|_, (_, _, time)| now.saturating_sub(*time) < PENDING_EXEC_SECS);
+    }
+
+    fn push(&mut self, event: ProcessEvent) {
+        if self.events.len() == EVENT_LOG_LEN {
+            self.events.pop_front();
+        }
+        self.events.push_back(event);
+    }
+
+    /// Whether `pid` started within the last few refreshes.
+    pub fn is_new(&self, pid: u32) -> bool {
+        self.fresh.contains_key(&pid)
+    }
+
+    /// Last snapshots of processes that exited within the last few refreshes.
+    pub fn recently_exited(&self) -> impl Iterator<Item = &ProcessInfo> {
+        self.exited.iter().map(|(info, _)| info)
+    }
+
+    /// Logged events, oldest first.
+    pub fn events(&self) -> &VecDeque<ProcessEvent> {
+        &self.events
+    }
+
+    /// Whether the netlink proc connector is feeding short-lived processes.
+    pub fn connector_active(&self) -> bool {
+        self.connector.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process(pid: u32, cpu: f32, rss: u64) -> ProcessInfo {
+        ProcessInfo {
+            cpu_percent: cpu,
+            rss: Bytes(rss),
+            start_time: 100,
+            command: format!("p{pid} --run"),
+            ..ProcessInfo::test(pid)
+        }
+    }
+
+    #[test]
+    fn diff_records_starts_exits_and_peaks() {
+        let mut tracker = LifecycleTracker::new(None);
+        tracker.update(&[process(1, 0.0, 10)], 100);
+        assert!(tracker.events().is_empty());
+
+        tracker.update(&[process(1, 0.0, 10), process(2, 80.0, 50)], 101);
+        assert!(tracker.is_new(2));
+        assert!(!tracker.is_new(1));
+        assert_eq!(tracker.events().len(), 1);
+        assert_eq!(tracker.events()[0].kind, EventKind::Started);
+
+        tracker.update(&[process(1, 0.0, 10), process(2, 20.0, 90)], 102);
+        tracker.update(&[process(1, 0.0, 10)], 105);
+        let exit = tracker.events().back().unwrap();
+        assert_eq!(exit.kind, EventKind::Exited);
+        assert_eq!(exit.pid, 2);
+        assert_eq!(exit.runtime_secs, Some(5));
+        assert_eq!(exit.peak_cpu, 80.0);
+        assert_eq!(exit.peak_rss, Bytes(90));
+        assert_eq!(
+            tracker.recently_exited().map(|p| p.pid).collect::<Vec<_>>(),
+            vec![2]
+        );
+    }
+
+    #[test]
+    fn reused_pid_logs_an_exit_and_a_start() {
+        let mut tracker = LifecycleTracker::new(None);
+        tracker.update(&[process(1, 0.0, 10)], 100);
+        let reused = ProcessInfo {
+            name: "other".into(),
+            start_time: 104,
+            ..process(1, 0.0, 10)
+        };
+        tracker.update(&[reused], 105);
+        let kinds: Vec<_> = tracker
+            .events()
+            .iter()
+            .map(|event| (event.kind, event.name.as_str()))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![(EventKind::Exited, "p1"), (EventKind::Started, "other")]
+        );
+        assert!(tracker.is_new(1));
+    }
+
+    #[test]
+    fn highlights_expire_after_a_few_ticks() {
+        let mut tracker = LifecycleTracker::new(None);
+        tracker.update(&[process(1, 0.0, 10), process(2, 0.0, 10)], 100);
+        tracker.update(&[process(1, 0.0, 10), process(3, 0.0, 10)], 101);
+        assert!(tracker.is_new(3));
+        assert_eq!(tracker.recently_exited().count(), 1);
+        for tick in 0..HIGHLIGHT_TICKS {
+            tracker.update(
+                &[process(1, 0.0, 10), process(3, 0.0, 10)],
+                102 + u64::from(tick),
+            );
+        }
+        assert!(!tracker.is_new(3));
+        assert_eq!(tracker.recently_exited().count(), 0);
+    }
+}
@@ -0,0 +1,269 @@
+//! Prometheus text-format `/metrics` endpoint for the CPU, memory and GPU collectors.
+
+use std::{
+    fmt::Write as _,
+    io::{self, BufRead, BufReader, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+use crate::{gpu::GpuStats, monitors::Monitors};
+
+/// Time allowed for a scraper to send its request line.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Extracts one per-GPU value, `None` when the device does not report it.
+type GpuReading = fn(&GpuStats) -> Option<f64>;
+
+/// Serves the most recently rendered metrics from a background thread.
+pub struct Exporter {
+    addr: SocketAddr,
+    metrics: Arc<Mutex<String>>,
+}
+
+impl Exporter {
+    /// Bind `addr` and start answering scrapes.
+    pub fn start(addr: SocketAddr) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        let addr = listener.local_addr()?;
+        let metrics = Arc::new(Mutex::new(String::new()));
+        let shared = Arc::clone(&metrics);
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                // A misbehaving scraper only costs its own connection.
+                let _ = respond(stream, &shared);
+            }
+        });
+        Ok(Self { addr, metrics })
+    }
+
+    /// Address the endpoint is listening on.
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Re-render the metrics served to the next scrape.
+    pub fn update(&self, monitors: &Monitors) {
+        let text = render(monitors);
+        if let Ok(mut metrics) = self.metrics.lock() {
+            *metrics = text;
+        }
+    }
+}
+
+fn respond(stream: TcpStream, metrics: &Mutex<String>) -> io::Result<()> {
+    stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+    let mut request = String::new();
+    BufReader::new(&stream).read_line(&mut request)?;
+    let mut parts = request.split_whitespace();
+    let (method, path) = (parts.next(), parts.next());
+    let (status, content_type, body) = match (method, path) {
+        (Some("GET"), Some("/metrics")) => (
+            "200 OK",
+            "text/plain; version=0.0.4; charset=utf-8",
+            metrics.lock().map(|text| text.clone()).unwrap_or_default(),
+        ),
+        (Some("GET"), Some("/")) => (
+            "200 OK",
+            "text/plain; charset=utf-8",
+            "rustop exporter: metrics at /metrics\n".to_string(),
+        ),
+        _ => (
+            "404 Not Found",
+            "text/plain; charset=utf-8",
+            "not found\n".to_string(),
+        ),
+    };
+    let mut stream = stream;
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )
+}
+
+/// One metric family in the exposition format.
+struct Family<'a> {
+    out: &'a mut String,
+    name: &'static str,
+}
+
+impl<'a> Family<'a> {
+    fn new(out: &'a mut String, name: &'static str, kind: &str, help: &str) -> Self {
+        let _ = writeln!(out, "# HELP {name} {help}");
+        let _ = writeln!(out, "# TYPE {name} {kind}");
+        Self { out, name }
+    }
+
+    fn sample(&mut self, labels: &[(&str, &str)], value: f64) {
+        self.out.push_str(self.name);
+        if !labels.is_empty() {
+            let labels: Vec<String> = labels
+                .iter()
+                .map(|(key, value)| format!("{key}=\"{}\"", escape(value)))
+                .collect();
+            let _ = write!(self.out, "{{{}}}", labels.join(","));
+        }
+        let _ = writeln!(self.out, " {value}");
+    }
+}
+
+/// Escape a label value: backslash, double quote and newline.
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', r"\\")
+        .replace('"', "\\\"")
+        .replace('\n', r"\n")
+}
+
+/// Render the current CPU, memory, load and GPU readings.
+pub fn render(monitors: &Monitors) -> String {
+    let mut out = String::new();
+    let cpu = &monitors.cpu;
+
+    Family::new(
+        &mut out,
+        "rustop_cpu_usage_ratio",
+        "gauge",
+        "CPU utilization across all threads (of the cgroup quota in container mode), 0-1.",
+    )
+    .sample(&[], f64::from(cpu.avg()) / 100.0);
+    let mut threads = Family::new(
+        &mut out,
+        "rustop_cpu_thread_usage_ratio",
+        "gauge",
+        "Utilization of one logical CPU, 0-1.",
+    );
+    for (id, usage) in cpu.ids().iter().zip(cpu.usages()) {
+        threads.sample(&[("cpu", &id.to_string())], f64::from(*usage) / 100.0);
+    }
+    if let Some(frequency) = cpu.frequency() {
+        Family::new(
+            &mut out,
+            "rustop_cpu_frequency_hertz",
+            "gauge",
+            "Average clock frequency across logical CPUs.",
+        )
+        .sample(&[], frequency.as_hz() as f64);
+    }
+
+    let (memory_used, memory_total) = cpu.memory_usage();
+    let (swap_used, swap_total) = cpu.swap_usage();
+    for (name, help, bytes) in [
+        (
+            "rustop_memory_used_bytes",
+            "Memory in use (cgroup working set in container mode).",
+            memory_used,
+        ),
+        (
+            "rustop_memory_total_bytes",
+            "Total memory (memory.max in container mode).",
+            memory_total,
+        ),
+        ("rustop_swap_used_bytes", "Swap in use.", swap_used),
+        ("rustop_swap_total_bytes", "Total swap.", swap_total),
+    ] {
+        Family::new(&mut out, name, "gauge", help).sample(&[], bytes.0 as f64);
+    }
+    if let Some(load) = monitors.system.load() {
+        for (name, help, value) in [
+            ("rustop_load1", "1-minute load average.", load.one),
+            ("rustop_load5", "5-minute load average.", load.five),
+            ("rustop_load15", "15-minute load average.", load.fifteen),
+        ] {
+            Family::new(&mut out, name, "gauge", help).sample(&[], value);
+        }
+    }
+
+    let gpus = monitors.gpu.stats();
+    if gpus.is_empty() {
+        return out;
+    }
+    let families: [(&'static str, &str, GpuReading); 4] = [
+        (
+            "rustop_gpu_utilization_ratio",
+            "GPU utilization, 0-1.",
+            |gpu| Some(f64::from(gpu.utilization) / 100.0),
+        ),
+        (
+            "rustop_gpu_memory_used_bytes",
+            "GPU memory in use.",
+            |gpu| Some(gpu.memory_used.0 as f64),
+        ),
+        (
+            "rustop_gpu_memory_total_bytes",
+            "Total GPU memory.",
+            |gpu| Some(gpu.memory_total.0 as f64),
+        ),
+        (
+            "rustop_gpu_temperature_celsius",
+            "GPU core temperature.",
+            |gpu| gpu.temperature.map(|t| f64::from(t.celsius())),
+        ),
+    ];
+    for (name, help, value) in families {
+        let mut family = Family::new(&mut out, name, "gauge", help);
+        for gpu in gpus {
+            if let Some(value) = value(gpu) {
+                let index = gpu.index.to_string();
+                let uuid = gpu.uuid.as_deref().unwrap_or_default();
+                family.sample(
+                    &[("gpu", &index), ("name", &gpu.name), ("uuid", uuid)],
+                    value,
+                );
+            }
+        }
+    }
+    let mut processes = Family::new(
+        &mut out,
+        "rustop_gpu_process_memory_bytes",
+        "gauge",
+        "GPU memory used by one process.",
+    );
+    for gpu in gpus {
+        for (pid, bytes) in &gpu.processes {
+            processes.sample(
+                &[("gpu", &gpu.index.to_string()), ("pid", &pid.to_string())],
+                bytes.0 as f64,
+            );
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http;
+
+    #[test]
+    fn family_writes_help_type_and_escaped_labels() {
+        let mut out = String::new();
+        let mut family = Family::new(&mut out, "rustop_test", "gauge", "A test metric.");
+        family.sample(&[], 1.5);
+        family.sample(&[("name", "Tesla \"T4\"\\x")], 2.0);
+        assert_eq!(
+            out,
+            "# HELP rustop_test A test metric.\n\
+             # TYPE rustop_test gauge\n\
+             rustop_test 1.5\n\
+             rustop_test{name=\"Tesla \\\"T4\\\"\\\\x\"} 2\n"
+        );
+    }
+
+    #[test]
+    fn serves_rendered_metrics() {
+        let monitors = Monitors::new();
+        let exporter = Exporter::start("127.0.0.1:0".parse().unwrap()).unwrap();
+        exporter.update(&monitors);
+        let addr = exporter.addr().to_string();
+
+        let body = http::get_tcp(&addr, "/metrics").unwrap();
+        assert!(body.contains("# TYPE rustop_cpu_usage_ratio gauge"));
+        assert!(body.contains("rustop_cpu_thread_usage_ratio{cpu=\"0\"}"));
+        assert!(body.contains("rustop_memory_total_bytes "));
+        assert!(http::get_tcp(&addr, "/nope").is_err());
+    }
+}
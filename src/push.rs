@@ -0,0 +1,452 @@
+//! Push outputs: InfluxDB line protocol and StatsD/DogStatsD gauges, fed from
+//! the same samples as the TUI.
+
+use std::{
+    fmt::Write as _,
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    net::UdpSocket,
+    sync::mpsc::{self, Receiver, SyncSender},
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    cli::{InfluxTarget, Options, StatsdOptions},
+    http,
+    monitors::Monitors,
+};
+
+/// Time between pushes unless `--push-interval` says otherwise.
+const DEFAULT_PUSH_INTERVAL: Duration = Duration::from_secs(10);
+/// Largest StatsD datagram; stays under a typical 1500-byte MTU.
+const MAX_DATAGRAM: usize = 1432;
+
+/// A field value; integers get the `i` suffix in line protocol.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FieldValue {
+    Int(u64),
+    Float(f64),
+}
+
+/// One measurement at one instant.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Point {
+    pub measurement: &'static str,
+    /// Identifying tag (`cpu=0`, `gpu=1`); also a StatsD name segment.
+    pub key: Option<(&'static str, String)>,
+    /// Descriptive tags sent only to InfluxDB.
+    pub tags: Vec<(&'static str, String)>,
+    pub fields: Vec<(&'static str, FieldValue)>,
+}
+
+/// CPU, memory, swap, load and GPU readings as points.
+pub fn points(monitors: &Monitors) -> Vec<Point> {
+    let cpu = &monitors.cpu;
+    let mut total = vec![("usage_percent", FieldValue::Float(f64::from(cpu.avg())))];
+    if let Some(frequency) = cpu.frequency() {
+        total.push(("frequency_hz", FieldValue::Int(frequency.as_hz())));
+    }
+    let mut points = vec![Point {
+        measurement: "cpu",
+        key: Some(("cpu", "total".to_string())),
+        tags: Vec::new(),
+        fields: total,
+    }];
+    points.extend(cpu.ids().iter().zip(cpu.usages()).map(|(id, usage)| Point {
+        measurement: "cpu",
+        key: Some(("cpu", id.to_string())),
+        tags: Vec::new(),
+        fields: vec![("usage_percent", FieldValue::Float(f64::from(*usage)))],
+    }));
+
+    let (memory_used, memory_total) = cpu.memory_usage();
+    let (swap_used, swap_total) = cpu.swap_usage();
+    for (measurement, used, total) in [
+        ("memory", memory_used, memory_total),
+        ("swap", swap_used, swap_total),
+    ] {
+        points.push(Point {
+            measurement,
+            key: None,
+            tags: Vec::new(),
+            fields: vec![
+                ("used", FieldValue::Int(used.0)),
+                ("total", FieldValue::Int(total.0)),
+            ],
+        });
+    }
+    if let Some(load) = monitors.system.load() {
+        points.push(Point {
+            measurement: "load",
+            key: None,
+            tags: Vec::new(),
+            fields: vec![
+                ("load1", FieldValue::Float(load.one)),
+                ("load5", FieldValue::Float(load.five)),
+                ("load15", FieldValue::Float(load.fifteen)),
+            ],
+        });
+    }
+
+    for gpu in monitors.gpu.stats() {
+        let mut fields = vec![
+            (
+                "utilization_percent",
+                FieldValue::Float(f64::from(gpu.utilization)),
+            ),
+            ("memory_used", FieldValue::Int(gpu.memory_used.0)),
+            ("memory_total", FieldValue::Int(gpu.memory_total.0)),
+        ];
+        if let Some(temperature) = gpu.temperature {
+            fields.push((
+                "temperature_celsius",
+                FieldValue::Float(f64::from(temperature.celsius())),
+            ));
+        }
+        let mut tags = vec![("name", gpu.name.clone())];
+        tags.extend(gpu.uuid.clone().map(|uuid| ("uuid", uuid)));
+        points.push(Point {
+            measurement: "gpu",
+            key: Some(("gpu", gpu.index.to_string())),
+            tags,
+            fields,
+        });
+    }
+    points
+}
+
+/// Escape commas, equals signs and spaces in a tag key or value.
+fn escape_tag(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, ',' | '=' | ' ' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Render points as InfluxDB line protocol with nanosecond timestamps.
+pub fn line_protocol(points: &[Point], host: Option<&str>, timestamp_ns: u128) -> String {
+    let mut out = String::new();
+    for point in points.iter().filter(|point| !point.fields.is_empty()) {
+        out.push_str(point.measurement);
+        let host = host.map(|host| ("host", host.to_string()));
+        for (key, value) in host.iter().chain(&point.key).chain(&point.tags) {
+            let _ = write!(out, ",{key}={}", escape_tag(value));
+        }
+        for (index, (name, value)) in point.fields.iter().enumerate() {
+            out.push(if index == 0 { ' ' } else { ',' });
+            let _ = match value {
+                FieldValue::Int(value) => write!(out, "{name}={value}i"),
+                FieldValue::Float(value) => write!(out, "{name}={value}"),
+            };
+        }
+        let _ = writeln!(out, " {timestamp_ns}");
+    }
+    out
+}
+
+/// Render points as StatsD gauges named `<prefix>.<measurement>[.<key>].<field>`,
+/// with DogStatsD `|#tags` when any are configured.
+pub fn statsd_lines(points: &[Point], prefix: &str, tags: &[String]) -> Vec<String> {
+    let suffix = if tags.is_empty() {
+        String::new()
+    } else {
+        format!("|#{}", tags.join(","))
+    };
+    let mut lines = Vec::new();
+    for point in points {
+        let mut name = String::new();
+        if !prefix.is_empty() {
+            name.push_str(prefix);
+            name.push('.');
+        }
+        name.push_str(point.measurement);
+        if let Some((_, key)) = &point.key {
+            name.push('.');
+            name.push_str(key);
+        }
+        for (field, value) in &point.fields {
+            let value = match value {
+                FieldValue::Int(value) => value.to_string(),
+                FieldValue::Float(value) => value.to_string(),
+            };
+            lines.push(format!("{name}.{field}:{value}|g{suffix}"));
+        }
+    }
+    lines
+}
+
+enum InfluxSink {
+    Writer(Box<dyn Write + Send>),
+    Http { addr: String, path: String },
+}
+
+/// The configured push outputs, owned by the worker thread.
+struct Sinks {
+    influx: Option<InfluxSink>,
+    statsd: Option<(UdpSocket, StatsdOptions)>,
+    host: Option<String>,
+}
+
+/// Queues each due sample for a worker thread that sends it, so a slow
+/// collector never stalls the caller.
+pub struct Pusher {
+    interval: Duration,
+    last_push: Option<Instant>,
+    samples: SyncSender<(Vec<Point>, u128)>,
+    results: Receiver<io::Result<()>>,
+}
+
+impl Pusher {
+    /// Open the configured outputs, or `None` when no push output is set.
+    pub fn new(options: &Options) -> io::Result<Option<Self>> {
+        let Some(mut sinks) = Sinks::open(options)? else {
+            return Ok(None);
+        };
+        // Room for one sample: while a push is in flight newer samples are dropped.
+        let (samples, queued) = mpsc::sync_channel::<(Vec<Point>, u128)>(1);
+        let (done, results) = mpsc::channel();
+        thread::spawn(move || {
+            for (points, timestamp_ns) in queued {
+                if done.send(sinks.push(&points, timestamp_ns)).is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(Some(Self {
+            interval: options.push_interval.unwrap_or(DEFAULT_PUSH_INTERVAL),
+            last_push: None,
+            samples,
+            results,
+        }))
+    }
+
+    /// Queue the current readings when the interval has elapsed. Returns the
+    /// outcome of the latest push finished since the previous call, if any.
+    pub fn update(&mut self, monitors: &Monitors) -> Option<io::Result<()>> {
+        let finished = self.results.try_iter().last();
+        if self
+            .last_push
+            .is_none_or(|at| at.elapsed() >= self.interval)
+        {
+            self.last_push = Some(Instant::now());
+            let timestamp_ns = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |elapsed| elapsed.as_nanos());
+            // A full queue means the previous push is still running; skip this sample.
+            let _ = self.samples.try_send((points(monitors), timestamp_ns));
+        }
+        finished
+    }
+}
+
+impl Sinks {
+    fn open(options: &Options) -> io::Result<Option<Self>> {
+        if options.influx.is_none() && options.statsd.is_none() {
+            return Ok(None);
+        }
+        let influx = match &options.influx {
+            None => None,
+            Some(InfluxTarget::Stdout) => Some(InfluxSink::Writer(Box::new(io::stdout()))),
+            Some(InfluxTarget::File(path)) => Some(InfluxSink::Writer(Box::new(
+                OpenOptions::new().create(true).append(true).open(path)?,
+            ))),
+            Some(InfluxTarget::Http { addr, path }) => Some(InfluxSink::Http {
+                addr: addr.clone(),
+                path: path.clone(),
+            }),
+        };
+        let statsd = match &options.statsd {
+            Some(statsd) => {
+                let socket = UdpSocket::bind(if statsd.addr.is_ipv4() {
+                    "0.0.0.0:0"
+                } else {
+                    "[::]:0"
+                })?;
+                socket.connect(statsd.addr)?;
+                Some((socket, statsd.clone()))
+            }
+            None => None,
+        };
+        let host = fs::read_to_string("/proc/sys/kernel/hostname")
+            .ok()
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty());
+        Ok(Some(Self {
+            influx,
+            statsd,
+            host,
+        }))
+    }
+
+    /// Send `points` to every output, reporting the first failure after trying all.
+    fn push(&mut self, points: &[Point], timestamp_ns: u128) -> io::Result<()> {
+        let mut result = Ok(());
+        if let Some(influx) = &mut self.influx {
+            let body = line_protocol(points, self.host.as_deref(), timestamp_ns);
+            let sent = match influx {
+                InfluxSink::Writer(writer) => writer
+                    .write_all(body.as_bytes())
+                    .and_then(|()| writer.flush()),
+                InfluxSink::Http { addr, path } => http::post_tcp(addr, path, &body).map(drop),
+            };
+            result = result.and(sent);
+        }
+        if let Some((socket, options)) = &self.statsd {
+            let lines = statsd_lines(points, &options.prefix, &options.tags);
+            let mut datagram = String::new();
+            for line in lines {
+                if !datagram.is_empty() && datagram.len() + 1 + line.len() > MAX_DATAGRAM {
+                    result = result.and(socket.send(datagram.as_bytes()).map(drop));
+                    datagram.clear();
+                }
+                if !datagram.is_empty() {
+                    datagram.push('\n');
+                }
+                datagram.push_str(&line);
+            }
+            if !datagram.is_empty() {
+                result = result.and(socket.send(datagram.as_bytes()).map(drop));
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        io::{BufRead, BufReader, Read},
+        net::TcpListener,
+        thread,
+    };
+
+    fn sample() -> Vec<Point> {
+        vec![
+            Point {
+                measurement: "cpu",
+                key: Some(("cpu", "0".to_string())),
+                tags: Vec::new(),
+                fields: vec![("usage_percent", FieldValue::Float(12.5))],
+            },
+            Point {
+                measurement: "gpu",
+                key: Some(("gpu", "0".to_string())),
+                tags: vec![("name", "Tesla T4,PCIe".to_string())],
+                fields: vec![
+                    ("memory_used", FieldValue::Int(1024)),
+                    ("temperature_celsius", FieldValue::Float(61.0)),
+                ],
+            },
+        ]
+    }
+
+    #[test]
+    fn line_protocol_escapes_tags_and_marks_integers() {
+        assert_eq!(
+            line_protocol(&sample(), Some("node 1"), 1_700_000_000_000_000_000),
+            "cpu,host=node\\ 1,cpu=0 usage_percent=12.5 1700000000000000000\n\
+             gpu,host=node\\ 1,gpu=0,name=Tesla\\ T4\\,PCIe memory_used=1024i,temperature_celsius=61 1700000000000000000\n"
+        );
+    }
+
+    #[test]
+    fn statsd_gauges_reach_a_udp_listener() {
+        let listener = UdpSocket::bind("127.0.0.1:0").unwrap();
+        listener
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let options = Options {
+            statsd: Some(StatsdOptions {
+                addr: listener.local_addr().unwrap(),
+                prefix: "lab".to_string(),
+                tags: vec!["env:ci".to_string()],
+            }),
+            ..Options::default()
+        };
+        let mut sinks = Sinks::open(&options).unwrap().unwrap();
+        sinks.push(&sample(), 0).unwrap();
+
+        let mut buffer = [0u8; MAX_DATAGRAM];
+        let len = listener.recv(&mut buffer).unwrap();
+        assert_eq!(
+            std::str::from_utf8(&buffer[..len]).unwrap(),
+            "lab.cpu.0.usage_percent:12.5|g|#env:ci\n\
+             lab.gpu.0.memory_used:1024|g|#env:ci\n\
+             lab.gpu.0.temperature_celsius:61|g|#env:ci"
+        );
+    }
+
+    #[test]
+    fn influx_lines_are_posted_to_an_http_stub() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(&stream);
+            let mut head = Vec::new();
+            let mut length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if let Some(value) = line.strip_prefix("Content-Length: ") {
+                    length = value.trim().parse().unwrap();
+                }
+                if line == "\r\n" {
+                    break;
+                }
+                head.push(line);
+            }
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body).unwrap();
+            write!(stream, "HTTP/1.1 204 No Content\r\n\r\n").unwrap();
+            (head[0].clone(), String::from_utf8(body).unwrap())
+        });
+
+        let options = Options {
+            influx: Some(InfluxTarget::Http {
+                addr,
+                path: "/write?db=rustop".to_string(),
+            }),
+            ..Options::default()
+        };
+        let mut sinks = Sinks::open(&options).unwrap().unwrap();
+        sinks.host = None;
+        sinks.push(&sample(), 42).unwrap();
+        let (request, body) = server.join().unwrap();
+
+        assert_eq!(request, "POST /write?db=rustop HTTP/1.1\r\n");
+        assert!(body.starts_with("cpu,cpu=0 usage_percent=12.5 42\n"));
+        assert_eq!(body.lines().count(), 2);
+    }
+
+    #[test]
+    fn worker_reports_failed_pushes() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        drop(listener);
+        let options = Options {
+            influx: Some(InfluxTarget::Http {
+                addr,
+                path: "/write".to_string(),
+            }),
+            ..Options::default()
+        };
+        let mut pusher = Pusher::new(&options).unwrap().unwrap();
+        let monitors = Monitors::new();
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let result = loop {
+            if let Some(result) = pusher.update(&monitors) {
+                break result;
+            }
+            assert!(Instant::now() < deadline, "push never finished");
+            thread::sleep(Duration::from_millis(10));
+        };
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,255 @@
+//! Headless logging of one flattened record per sample to NDJSON or CSV.
+
+use std::{
+    error::Error,
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::PathBuf,
+    thread,
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+use serde_json::{Map, Value};
+
+use crate::{
+    cli::{RecordFormat, RecordOptions},
+    monitors::Monitors,
+};
+
+/// Sample until interrupted, writing one record per interval.
+pub fn run(options: RecordOptions) -> Result<(), Box<dyn Error>> {
+    let mut monitors = Monitors::new();
+    let mut recorder = Recorder::open(options.clone())?;
+    let start = Instant::now();
+    for sample in 1u32.. {
+        // Sleep to the next multiple of the interval so slow samples do not drift.
+        let due = start + options.interval * sample;
+        thread::sleep(due.saturating_duration_since(Instant::now()));
+        monitors.refresh();
+        recorder.write(epoch_millis(), &fields(&monitors))?;
+    }
+    Ok(())
+}
+
+fn epoch_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_millis() as u64)
+}
+
+/// Flatten the headline metrics into named columns: overall and per-thread
+/// CPU, memory, swap, load and each GPU's utilization, VRAM and temperature.
+pub fn fields(monitors: &Monitors) -> Vec<(String, Value)> {
+    let mut fields = vec![("cpu_percent".to_string(), Value::from(monitors.cpu.avg()))];
+    for (id, usage) in monitors.cpu.ids().iter().zip(monitors.cpu.usages()) {
+        fields.push((format!("cpu{id}_percent"), Value::from(*usage)));
+    }
+    let (memory_used, memory_total) = monitors.cpu.memory_usage();
+    let (swap_used, swap_total) = monitors.cpu.swap_usage();
+    fields.extend([
+        ("memory_used".to_string(), Value::from(memory_used.0)),
+        ("memory_total".to_string(), Value::from(memory_total.0)),
+        ("swap_used".to_string(), Value::from(swap_used.0)),
+        ("swap_total".to_string(), Value::from(swap_total.0)),
+    ]);
+    let load = monitors.system.load();
+    fields.extend([
+        ("load1".to_string(), Value::from(load.map(|load| load.one))),
+        ("load5".to_string(), Value::from(load.map(|load| load.five))),
+        (
+            "load15".to_string(),
+            Value::from(load.map(|load| load.fifteen)),
+        ),
+    ]);
+    for gpu in monitors.gpu.stats() {
+        let prefix = format!("gpu{}", gpu.index);
+        fields.extend([
+            (
+                format!("{prefix}_utilization_percent"),
+                Value::from(gpu.utilization),
+            ),
+            (
+                format!("{prefix}_memory_used"),
+                Value::from(gpu.memory_used.0),
+            ),
+            (
+                format!("{prefix}_memory_total"),
+                Value::from(gpu.memory_total.0),
+            ),
+            (
+                format!("{prefix}_temperature_celsius"),
+                Value::from(gpu.temperature.map(|t| t.celsius())),
+            ),
+        ]);
+    }
+    fields
+}
+
+/// Writes records to stdout or a file, rotating the file by size or age.
+pub struct Recorder {
+    options: RecordOptions,
+    writer: Box<dyn Write>,
+    /// CSV columns, fixed by the first sample and repeated atop each file.
+    header: Option<Vec<String>>,
+    written: u64,
+    opened: Instant,
+    opened_millis: u64,
+}
+
+impl Recorder {
+    /// Create (truncating) the output file, or use stdout when none is given.
+    pub fn open(options: RecordOptions) -> io::Result<Self> {
+        let writer = open_writer(options.output.as_ref())?;
+        Ok(Self {
+            options,
+            writer,
+            header: None,
+            written: 0,
+            opened: Instant::now(),
+            opened_millis: epoch_millis(),
+        })
+    }
+
+    /// Append one record, first rotating if it would exceed a limit.
+    pub fn write(&mut self, timestamp_millis: u64, fields: &[(String, Value)]) -> io::Result<()> {
+        let timestamp = timestamp_millis as f64 / 1000.0;
+        let mut row = match self.options.format {
+            RecordFormat::Ndjson => {
+                let mut record = Map::new();
+                record.insert("timestamp".to_string(), Value::from(timestamp));
+                for (name, value) in fields {
+                    record.insert(name.clone(), value.clone());
+                }
+                Value::Object(record).to_string()
+            }
+            RecordFormat::Csv => {
+                // Columns are fixed by the first sample; later fields are matched by name.
+                let header = self
+                    .header
+                    .get_or_insert_with(|| fields.iter().map(|(name, _)| name.clone()).collect());
+                let mut row = format!("{timestamp:.3}");
+                for column in header.iter() {
+                    row.push(',');
+                    match fields.iter().find(|(name, _)| name == column) {
+                        Some((_, Value::Null)) | None => {}
+                        Some((_, value)) => row.push_str(&value.to_string()),
+                    }
+                }
+                row
+            }
+        };
+        row.push('\n');
+
+        let full = self
+            .options
+            .rotate_size
+            .is_some_and(|limit| self.written + row.len() as u64 > limit);
+        let old = self
+            .options
+            .rotate_every
+            .is_some_and(|every| self.opened.elapsed() >= every);
+        // Every file keeps at least one record, even when a row exceeds the size limit.
+        if self.written > 0 && (full || old) {
+            self.rotate()?;
+        }
+        if self.written == 0 {
+            if let Some(header) = &self.header {
+                row.insert_str(0, &format!("timestamp,{}\n", header.join(",")));
+            }
+        }
+        self.writer.write_all(row.as_bytes())?;
+        self.writer.flush()?;
+        self.written += row.len() as u64;
+        Ok(())
+    }
+
+    /// Move the current file aside as `<output>.<opened unix millis>` and start a new one.
+    fn rotate(&mut self) -> io::Result<()> {
+        let Some(path) = self.options.output.clone() else {
+            return Ok(());
+        };
+        let mut rotated = path.clone().into_os_string();
+        rotated.push(format!(".{}", self.opened_millis));
+        fs::rename(&path, rotated)?;
+        self.writer = open_writer(Some(&path))?;
+        self.written = 0;
+        self.opened = Instant::now();
+        self.opened_millis = epoch_millis();
+        Ok(())
+    }
+}
+
+fn open_writer(path: Option<&PathBuf>) -> io::Result<Box<dyn Write>> {
+    Ok(match path {
+        Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+        None => Box::new(io::stdout()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDir;
+
+    fn sample(cpu: f32, gpu: Option<f64>) -> Vec<(String, Value)> {
+        vec![
+            ("cpu_percent".to_string(), Value::from(cpu)),
+            ("memory_used".to_string(), Value::from(1024u64)),
+            ("gpu0_temperature_celsius".to_string(), Value::from(gpu)),
+        ]
+    }
+
+    #[test]
+    fn writes_csv_and_ndjson_records() {
+        let dir = TempDir::new("record");
+        let csv = dir.path().join("run.csv");
+        let ndjson = dir.path().join("run.ndjson");
+        for (path, format) in [(&csv, RecordFormat::Csv), (&ndjson, RecordFormat::Ndjson)] {
+            let mut recorder = Recorder::open(RecordOptions {
+                format,
+                output: Some(path.clone()),
+                ..RecordOptions::default()
+            })
+            .unwrap();
+            recorder.write(1_500, &sample(12.5, Some(61.0))).unwrap();
+            recorder.write(2_500, &sample(50.0, None)).unwrap();
+        }
+        let csv = fs::read_to_string(csv).unwrap();
+        let ndjson = fs::read_to_string(ndjson).unwrap();
+
+        assert_eq!(
+            csv,
+            "timestamp,cpu_percent,memory_used,gpu0_temperature_celsius\n\
+             1.500,12.5,1024,61.0\n\
+             2.500,50.0,1024,\n"
+        );
+        let first: Value = serde_json::from_str(ndjson.lines().next().unwrap()).unwrap();
+        assert_eq!(first["timestamp"], 1.5);
+        assert_eq!(first["memory_used"], 1024);
+        assert_eq!(ndjson.lines().count(), 2);
+    }
+
+    #[test]
+    fn rotates_by_size_with_a_fresh_header() {
+        let dir = TempDir::new("rotate");
+        let path = dir.path().join("run.csv");
+        let mut recorder = Recorder::open(RecordOptions {
+            format: RecordFormat::Csv,
+            output: Some(path.clone()),
+            rotate_size: Some(64),
+            ..RecordOptions::default()
+        })
+        .unwrap();
+        recorder.write(1_000, &sample(1.0, None)).unwrap();
+        recorder.write(2_000, &sample(2.0, None)).unwrap();
+        let current = fs::read_to_string(&path).unwrap();
+        let rotated = fs::read_dir(dir.path())
+            .unwrap()
+            .filter(|entry| entry.as_ref().unwrap().path() != path)
+            .count();
+
+        assert_eq!(rotated, 1);
+        assert!(current.starts_with("timestamp,cpu_percent"));
+        assert!(current.contains("2.000,2.0"));
+    }
+}
@@ -0,0 +1,364 @@
+//! One-shot JSON snapshot of every collector, for scripts and health checks.
+
+use std::{
+    error::Error,
+    io::{self, Write},
+    thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use serde_json::{json, Value};
+
+use crate::{
+    monitors::Monitors,
+    pressure::{Pressure, PressureLine, PressureSet, Resource},
+    process::anomaly::AnomalyKind,
+    units::ByteRate,
+};
+
+/// Gap between the two samples that rates and CPU percentages are computed over.
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Sample twice, print the snapshot to stdout and return without touching the terminal.
+pub fn run() -> Result<(), Box<dyn Error>> {
+    let mut monitors = Monitors::new();
+    monitors.process.set_smaps_enabled(true);
+    monitors.cgroups.set_enabled(true);
+    monitors.docker.set_enabled(true);
+    monitors.docker.wait_for_poll();
+    monitors.refresh();
+    thread::sleep(SAMPLE_INTERVAL);
+    monitors.refresh();
+    monitors.kube.wait_for_poll();
+    let document = serde_json::to_string_pretty(&collect(&monitors))?;
+    writeln!(io::stdout().lock(), "{document}")?;
+    Ok(())
+}
+
+/// Every metric the monitors currently hold. Sizes are in bytes, rates in
+/// bytes per second and temperatures in degrees Celsius.
+pub fn collect(monitors: &Monitors) -> Value {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs());
+    json!({
+        "timestamp": timestamp,
+        "system": system(monitors),
+        "cpu": cpu(monitors),
+        "memory": memory(monitors),
+        "swap": swap(monitors),
+        "pressure": {
+            "system": pressure_set(monitors.pressure.system()),
+            "cgroup": monitors.pressure.cgroup().map(pressure_set),
+        },
+        "gpus": gpus(monitors),
+        "processes": processes(monitors),
+        "warnings": warnings(monitors),
+        "cgroups": cgroups(monitors),
+        "containers": containers(monitors),
+    })
+}
+
+fn rate(rate: Option<ByteRate>) -> Option<f64> {
+    rate.map(|rate| rate.0)
+}
+
+fn system(monitors: &Monitors) -> Value {
+    let system = &monitors.system;
+    json!({
+        "uptime_secs": system.uptime_secs(),
+        "boot_time": system.boot_time(),
+        "load": system.load().map(|load| json!({
+            "one": load.one,
+            "five": load.five,
+            "fifteen": load.fifteen,
+            "running": load.running,
+            "total": load.total,
+        })),
+        "context_switches_per_sec": system.ctxt_rate(),
+        "interrupts_per_sec": system.intr_rate(),
+    })
+}
+
+fn cpu(monitors: &Monitors) -> Value {
+    let cpu = &monitors.cpu;
+    let placements: Vec<(usize, Value)> = cpu
+        .topology()
+        .groups()
+        .iter()
+        .flat_map(|group| {
+            group.cores.iter().flat_map(move |core| {
+                core.cpus.iter().map(move |&id| {
+                    let placement = json!({
+                        "package": group.package,
+                        "node": group.node,
+                        "core": core.core,
+                        "kind": Some(core.kind.label()).filter(|kind| !kind.is_empty()),
+                    });
+                    (id, placement)
+                })
+            })
+        })
+        .collect();
+    let threads: Vec<Value> = cpu
+        .ids()
+        .iter()
+        .zip(cpu.usages())
+        .map(|(&id, usage)| {
+            let mut thread = json!({ "cpu": id, "usage_percent": usage });
+            if let Some((_, placement)) = placements.iter().find(|(cpu, _)| *cpu == id) {
+                thread["topology"] = placement.clone();
+            }
+            thread
+        })
+        .collect();
+    json!({
+        "usage_percent": cpu.avg(),
+        "frequency_hz": cpu.frequency().map(|frequency| frequency.as_hz()),
+        "cpus": cpu.cpus(),
+        "threads": threads,
+        "container": cpu.container().map(|container| json!({
+            "cpu_quota": container.cpu_quota(),
+            "cpu_percent": container.cpu_percent(),
+            "memory_max": container.memory_max().map(|bytes| bytes.0),
+            "cpuset": container.cpuset(),
+        })),
+    })
+}
+
+fn memory(monitors: &Monitors) -> Value {
+    let (used, total) = monitors.cpu.memory_usage();
+    let mut memory = json!({ "used": used.0, "total": total.0 });
+    if let Some(info) = monitors.memory.info() {
+        memory["meminfo"] = json!({
+            "total": info.total.0,
+            "free": info.free.0,
+            "available": info.available.0,
+            "used": info.used().0,
+            "buffers": info.buffers.0,
+            "cached": info.cached.0,
+            "page_cache": info.page_cache().0,
+            "shmem": info.shmem.0,
+            "slab_reclaimable": info.slab_reclaimable.0,
+            "slab_unreclaimable": info.slab_unreclaimable.0,
+            "dirty": info.dirty.0,
+            "writeback": info.writeback.0,
+            "hugepages_total": info.hugepages_total,
+            "hugepages_free": info.hugepages_free,
+            "hugepage_size": info.hugepage_size.0,
+            "committed": info.committed.0,
+            "commit_limit": info.commit_limit.0,
+        });
+    }
+    memory
+}
+
+fn swap(monitors: &Monitors) -> Value {
+    let (used, total) = monitors.cpu.swap_usage();
+    let swap = &monitors.swap;
+    json!({
+        "used": used.0,
+        "total": total.0,
+        "in_rate": rate(swap.swap_in_rate()),
+        "out_rate": rate(swap.swap_out_rate()),
+        "zram": swap.zram().iter().map(|device| json!({
+            "name": device.name,
+            "disk_size": device.disk_size.0,
+            "orig_data_size": device.orig_data_size.0,
+            "compr_data_size": device.compr_data_size.0,
+            "mem_used_total": device.mem_used_total.0,
+            "compression_ratio": device.compression_ratio(),
+        })).collect::<Vec<_>>(),
+        "zswap": swap.zswap().map(|zswap| json!({
+            "pool_size": zswap.pool_size.0,
+            "stored": zswap.stored.0,
+        })),
+    })
+}
+
+fn pressure_line(line: Option<PressureLine>) -> Value {
+    json!(line.map(|line| json!({
+        "avg10": line.avg10,
+        "avg60": line.avg60,
+        "avg300": line.avg300,
+        "total_us": line.total,
+    })))
+}
+
+fn pressure(pressure: Option<Pressure>) -> Value {
+    json!(pressure.map(|pressure| json!({
+        "some": pressure_line(pressure.some),
+        "full": pressure_line(pressure.full),
+    })))
+}
+
+fn pressure_set(set: &PressureSet) -> Value {
+    let mut value = json!({ "label": set.label() });
+    for resource in Resource::ALL {
+        value[resource.name()] = pressure(set.track(resource).latest());
+    }
+    value
+}
+
+fn gpus(monitors: &Monitors) -> Value {
+    monitors
+        .gpu
+        .stats()
+        .iter()
+        .map(|gpu| {
+            json!({
+                "index": gpu.index,
+                "name": gpu.name,
+                "uuid": gpu.uuid,
+                "utilization_percent": gpu.utilization,
+                "memory_used": gpu.memory_used.0,
+                "memory_total": gpu.memory_total.0,
+                "temperature_celsius": gpu.temperature.map(|t| t.celsius()),
+                "processes": gpu.processes.iter().map(|(pid, bytes)| json!({
+                    "pid": pid,
+                    "memory": bytes.0,
+                })).collect::<Vec<_>>(),
+            })
+        })
+        .collect()
+}
+
+fn processes(monitors: &Monitors) -> Value {
+    monitors
+        .process
+        .processes()
+        .iter()
+        .map(|process| {
+            json!({
+                "pid": process.pid,
+                "ppid": process.ppid,
+                "name": process.name,
+                "state": process.state.to_string(),
+                "uid": process.uid,
+                "user": process.user,
+                "cpu_percent": process.cpu_percent,
+                "rss": process.rss.0,
+                "pss": process.memory.map(|memory| memory.pss.0),
+                "uss": process.memory.map(|memory| memory.uss.0),
+                "swap": process.memory.map(|memory| memory.swap.0),
+                "read_rate": rate(process.read_rate),
+                "write_rate": rate(process.write_rate),
+                "net_rate": rate(process.net_rate),
+                "threads": process.threads,
+                "start_time": process.start_time,
+                "command": process.command,
+                "cgroup": process.cgroup,
+                "pod": monitors.kube.pod_label(process.cgroup.as_deref()),
+                "gpu_memory": monitors.gpu.process_memory(process.pid).map(|bytes| bytes.0),
+            })
+        })
+        .collect()
+}
+
+fn warnings(monitors: &Monitors) -> Value {
+    monitors
+        .process
+        .anomalies()
+        .anomalies()
+        .iter()
+        .map(|anomaly| {
+            let mut value = json!({
+                "pid": anomaly.pid,
+                "name": anomaly.name,
+                "wchan": anomaly.wchan,
+            });
+            let (kind, detail) = match anomaly.kind {
+                AnomalyKind::Zombie { ppid } => ("zombie", json!({ "ppid": ppid })),
+                AnomalyKind::Blocked { secs } => ("blocked", json!({ "secs": secs })),
+                AnomalyKind::RssGrowth { from, to } => {
+                    ("rss_growth", json!({ "from": from.0, "to": to.0 }))
+                }
+                AnomalyKind::FdGrowth { from, to } => {
+                    ("fd_growth", json!({ "from": from, "to": to }))
+                }
+            };
+            value["kind"] = json!(kind);
+            value["detail"] = detail;
+            value
+        })
+        .collect()
+}
+
+fn cgroups(monitors: &Monitors) -> Value {
+    monitors
+        .cgroups
+        .nodes()
+        .iter()
+        .map(|node| {
+            let mut value = json!({
+                "path": node.path,
+                "cpu_percent": node.cpu_percent,
+                "memory_current": node.memory_current.map(|bytes| bytes.0),
+                "memory_max": node.memory_max.map(|bytes| bytes.0),
+                "read_rate": rate(node.read_rate),
+                "write_rate": rate(node.write_rate),
+                "pids": node.pids,
+            });
+            for (resource, pressure_value) in Resource::ALL.iter().zip(node.pressure) {
+                value[format!("{}_pressure", resource.name())] = pressure(pressure_value);
+            }
+            value
+        })
+        .collect()
+}
+
+fn containers(monitors: &Monitors) -> Value {
+    monitors
+        .docker
+        .containers()
+        .iter()
+        .map(|container| {
+            json!({
+                "id": container.id,
+                "name": container.name,
+                "image": container.image,
+                "pid": container.pid,
+                "cpu_percent": container.cpu_percent,
+                "memory": container.memory.map(|bytes| bytes.0),
+                "memory_max": container.memory_max.map(|bytes| bytes.0),
+                "net_rate": rate(container.net_rate),
+                "gpu_spec": container.gpu_spec,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn collect_covers_every_collector() {
+        let monitors = Monitors::new();
+        let snapshot = collect(&monitors);
+        for key in [
+            "system",
+            "cpu",
+            "memory",
+            "swap",
+            "pressure",
+            "gpus",
+            "processes",
+            "warnings",
+            "cgroups",
+            "containers",
+        ] {
+            assert!(snapshot.get(key).is_some(), "missing {key}");
+        }
+        assert_eq!(
+            snapshot["cpu"]["threads"].as_array().map(Vec::len),
+            Some(monitors.cpu.usages().len())
+        );
+        let own_pid = std::process::id();
+        assert!(snapshot["processes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|process| process["pid"] == own_pid));
+    }
+}
@@ -0,0 +1,255 @@
+//! Swap activity, zram devices and zswap pool usage.
+
+use std::{fs, path::Path, time::Instant};
+
+use crate::{
+    memory::parse_meminfo_fields,
+    units::{ByteRate, Bytes},
+};
+
+/// Compression stats for a single zram device.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ZramDevice {
+    pub name: String,
+    pub disk_size: Bytes,
+    pub orig_data_size: Bytes,
+    pub compr_data_size: Bytes,
+    pub mem_used_total: Bytes,
+}
+
+impl ZramDevice {
+    /// Original/compressed size ratio, `None` when nothing is stored.
+    pub fn compression_ratio(&self) -> Option<f64> {
+        if self.compr_data_size == Bytes(0) {
+            None
+        } else {
+            Some(self.orig_data_size.ratio_of(self.compr_data_size))
+        }
+    }
+}
+
+/// zswap compressed pool usage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ZswapInfo {
+    pub pool_size: Bytes,
+    pub stored: Bytes,
+}
+
+/// Cumulative swap page counters from `/proc/vmstat`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct SwapCounters {
+    pswpin: u64,
+    pswpout: u64,
+}
+
+/// Tracks swap-in/out rates, zram devices and zswap.
+pub struct SwapMonitor {
+    page_size: u64,
+    swap_in_rate: Option<ByteRate>,
+    swap_out_rate: Option<ByteRate>,
+    last_counters: Option<(SwapCounters, Instant)>,
+    zram: Vec<ZramDevice>,
+    zswap: Option<ZswapInfo>,
+}
+
+impl SwapMonitor {
+    /// Construct a new monitor and perform an initial refresh.
+    pub fn new() -> Self {
+        // SAFETY: sysconf has no preconditions and only reads system configuration.
+        let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+        let mut monitor = Self {
+            page_size: u64::try_from(page_size).unwrap_or(4096),
+            swap_in_rate: None,
+            swap_out_rate: None,
+            last_counters: None,
+            zram: Vec::new(),
+            zswap: None,
+        };
+        monitor.refresh();
+        monitor
+    }
+
+    /// Refresh swap rates, zram devices and zswap pool usage.
+    pub fn refresh(&mut self) {
+        let now = Instant::now();
+        match fs::read_to_string("/proc/vmstat")
+            .ok()
+            .and_then(|text| parse_vmstat(&text))
+        {
+            Some(counters) => {
+                if let Some((previous, at)) = self.last_counters {
+                    let elapsed = now.duration_since(at).as_secs_f64();
+                    let pages = |count: u64| Bytes(count.saturating_mul(self.page_size));
+                    self.swap_in_rate =
+                        ByteRate::between(pages(previous.pswpin), pages(counters.pswpin), elapsed);
+                    self.swap_out_rate = ByteRate::between(
+                        pages(previous.pswpout),
+                        pages(counters.pswpout),
+                        elapsed,
+                    );
+                }
+                self.last_counters = Some((counters, now));
+            }
+            None => {
+                self.swap_in_rate = None;
+                self.swap_out_rate = None;
+                self.last_counters = None;
+            }
+        }
+
+        self.zram = read_zram_devices(Path::new("/sys/block"));
+        self.zswap = read_zswap();
+    }
+
+    /// Swap-in throughput since the previous refresh.
+    pub fn swap_in_rate(&self) -> Option<ByteRate> {
+        self.swap_in_rate
+    }
+
+    /// Swap-out throughput since the previous refresh.
+    pub fn swap_out_rate(&self) -> Option<ByteRate> {
+        self.swap_out_rate
+    }
+
+    /// Initialized zram devices.
+    pub fn zram(&self) -> &[ZramDevice] {
+        &self.zram
+    }
+
+    /// zswap pool usage, `None` when zswap is disabled.
+    pub fn zswap(&self) -> Option<ZswapInfo> {
+        self.zswap
+    }
+}
+
+fn parse_vmstat(text: &str) -> Option<SwapCounters> {
+    let mut pswpin = None;
+    let mut pswpout = None;
+    for line in text.lines() {
+        match line.split_once(' ') {
+            Some(("pswpin", value)) => pswpin = value.trim().parse().ok(),
+            Some(("pswpout", value)) => pswpout = value.trim().parse().ok(),
+            _ => {}
+        }
+    }
+    Some(SwapCounters {
+        pswpin: pswpin?,
+        pswpout: pswpout?,
+    })
+}
+
+/// Parse the leading `orig_data_size compr_data_size mem_used_total` fields of `mm_stat`.
+fn parse_mm_stat(text: &str) -> Option<(Bytes, Bytes, Bytes)> {
+    let mut fields = text
+        .split_whitespace()
+        .map(|v| v.parse::<u64>().ok().map(Bytes));
+    Some((fields.next()??, fields.next()??, fields.next()??))
+}
+
+fn read_zram_devices(block_root: &Path) -> Vec<ZramDevice> {
+    let Ok(entries) = fs::read_dir(block_root) else {
+        return Vec::new();
+    };
+    let mut devices: Vec<ZramDevice> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_str()?.to_string();
+            if !name.starts_with("zram") {
+                return None;
+            }
+            let disk_size = Bytes(
+                fs::read_to_string(entry.path().join("disksize"))
+                    .ok()?
+                    .trim()
+                    .parse()
+                    .ok()?,
+            );
+            if disk_size == Bytes(0) {
+                return None;
+            }
+            let (orig_data_size, compr_data_size, mem_used_total) =
+                parse_mm_stat(&fs::read_to_string(entry.path().join("mm_stat")).ok()?)?;
+            Some(ZramDevice {
+                name,
+                disk_size,
+                orig_data_size,
+                compr_data_size,
+                mem_used_total,
+            })
+        })
+        .collect();
+    devices.sort_by(|a, b| a.name.cmp(&b.name));
+    devices
+}
+
+fn read_zswap() -> Option<ZswapInfo> {
+    let enabled = fs::read_to_string("/sys/module/zswap/parameters/enabled").ok()?;
+    if enabled.trim() != "Y" {
+        return None;
+    }
+    let meminfo = fs::read_to_string("/proc/meminfo").ok()?;
+    let fields = parse_meminfo_fields(&meminfo);
+    Some(ZswapInfo {
+        pool_size: Bytes::from_kib(fields.get("Zswap").copied().unwrap_or(0)),
+        stored: Bytes::from_kib(fields.get("Zswapped").copied().unwrap_or(0)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDir;
+
+    #[test]
+    fn parse_vmstat_reads_swap_counters() {
+        let text = "nr_free_pages 1\npswpin 12\npswpout 34\npgpgin 5\n";
+        assert_eq!(
+            parse_vmstat(text),
+            Some(SwapCounters {
+                pswpin: 12,
+                pswpout: 34,
+            })
+        );
+        assert_eq!(parse_vmstat("pswpin 1\n"), None);
+    }
+
+    #[test]
+    fn parse_mm_stat_and_ratio() {
+        let (orig, compr, used) =
+            parse_mm_stat("  4194304   1048576   1200000        0  1200000      10        0")
+                .unwrap();
+        let device = ZramDevice {
+            name: "zram0".into(),
+            disk_size: Bytes(8 << 30),
+            orig_data_size: orig,
+            compr_data_size: compr,
+            mem_used_total: used,
+        };
+        assert_eq!(device.compression_ratio(), Some(4.0));
+        assert_eq!(device.mem_used_total, Bytes(1_200_000));
+        assert_eq!(parse_mm_stat("1 2"), None);
+    }
+
+    #[test]
+    fn read_zram_devices_skips_unused_and_other_devices() {
+        let root = TempDir::new("zram");
+        root.write("zram1/disksize", "8589934592\n");
+        root.write("zram1/mm_stat", "4096 1024 8192 0 8192 0 0 0 0\n");
+        root.write("zram0/disksize", "0\n");
+        root.write("zram0/mm_stat", "0 0 0 0 0 0 0 0 0\n");
+        root.write("sda/size", "1000\n");
+
+        let devices = read_zram_devices(root.path());
+
+        assert_eq!(
+            devices,
+            vec![ZramDevice {
+                name: "zram1".into(),
+                disk_size: Bytes(8 << 30),
+                orig_data_size: Bytes(4096),
+                compr_data_size: Bytes(1024),
+                mem_used_total: Bytes(8192),
+            }]
+        );
+    }
+}
@@ -0,0 +1,195 @@
+//! System-wide kernel counters: load averages, uptime and scheduler activity.
+
+use std::{fs, time::Instant};
+
+/// Load averages and task counts parsed from `/proc/loadavg`.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct LoadAvg {
+    pub one: f64,
+    pub five: f64,
+    pub fifteen: f64,
+    pub running: u64,
+    pub total: u64,
+}
+
+/// Cumulative counters parsed from `/proc/stat`.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct StatCounters {
+    ctxt: u64,
+    intr: u64,
+    btime: u64,
+}
+
+/// Captures load, uptime and context switch/interrupt rates for display.
+pub struct SystemMonitor {
+    load: Option<LoadAvg>,
+    uptime_secs: Option<f64>,
+    boot_time: Option<u64>,
+    ctxt_rate: Option<f64>,
+    intr_rate: Option<f64>,
+    last_counters: Option<(StatCounters, Instant)>,
+}
+
+impl SystemMonitor {
+    /// Construct a new monitor and perform an initial refresh.
+    pub fn new() -> Self {
+        let mut monitor = Self {
+            load: None,
+            uptime_secs: None,
+            boot_time: None,
+            ctxt_rate: None,
+            intr_rate: None,
+            last_counters: None,
+        };
+        monitor.refresh();
+        monitor
+    }
+
+    /// Refresh load averages, uptime and per-second counter rates.
+    pub fn refresh(&mut self) {
+        self.load = fs::read_to_string("/proc/loadavg")
+            .ok()
+            .and_then(|text| parse_loadavg(&text));
+        self.uptime_secs = fs::read_to_string("/proc/uptime")
+            .ok()
+            .and_then(|text| parse_uptime(&text));
+
+        let Some(counters) = fs::read_to_string("/proc/stat")
+            .ok()
+            .and_then(|text| parse_stat(&text))
+        else {
+            self.ctxt_rate = None;
+            self.intr_rate = None;
+            self.last_counters = None;
+            return;
+        };
+
+        let now = Instant::now();
+        self.boot_time = Some(counters.btime);
+        if let Some((previous, at)) = self.last_counters {
+            let elapsed = now.duration_since(at).as_secs_f64();
+            if elapsed > 0.0 {
+                self.ctxt_rate = Some(counters.ctxt.saturating_sub(previous.ctxt) as f64 / elapsed);
+                self.intr_rate = Some(counters.intr.saturating_sub(previous.intr) as f64 / elapsed);
+            }
+        }
+        self.last_counters = Some((counters, now));
+    }
+
+    /// 1/5/15-minute load averages and running/total task counts.
+    pub fn load(&self) -> Option<LoadAvg> {
+        self.load
+    }
+
+    /// Seconds since boot.
+    pub fn uptime_secs(&self) -> Option<f64> {
+        self.uptime_secs
+    }
+
+    /// Boot time as seconds since the Unix epoch.
+    pub fn boot_time(&self) -> Option<u64> {
+        self.boot_time
+    }
+
+    /// Context switches per second since the previous refresh.
+    pub fn ctxt_rate(&self) -> Option<f64> {
+        self.ctxt_rate
+    }
+
+    /// Interrupts per second since the previous refresh.
+    pub fn intr_rate(&self) -> Option<f64> {
+        self.intr_rate
+    }
+}
+
+/// Boot time as seconds since the Unix epoch, read from `/proc/stat`.
+pub fn read_boot_time() -> Option<u64> {
+    fs::read_to_string("/proc/stat")
+        .ok()
+        .and_then(|text| parse_stat(&text))
+        .map(|counters| counters.btime)
+}
+
+fn parse_loadavg(text: &str) -> Option<LoadAvg> {
+    let mut fields = text.split_whitespace();
+    let one = fields.next()?.parse().ok()?;
+    let five = fields.next()?.parse().ok()?;
+    let fifteen = fields.next()?.parse().ok()?;
+    let (running, total) = fields.next()?.split_once('/')?;
+    Some(LoadAvg {
+        one,
+        five,
+        fifteen,
+        running: running.parse().ok()?,
+        total: total.parse().ok()?,
+    })
+}
+
+fn parse_uptime(text: &str) -> Option<f64> {
+    text.split_whitespace().next()?.parse().ok()
+}
+
+fn parse_stat(text: &str) -> Option<StatCounters> {
+    let mut ctxt = None;
+    let mut intr = None;
+    let mut btime = None;
+    for line in text.lines() {
+        let mut fields = line.split_whitespace();
+        match fields.next() {
+            Some("ctxt") => ctxt = fields.next().and_then(|v| v.parse().ok()),
+            Some("intr") => intr = fields.next().and_then(|v| v.parse().ok()),
+            Some("btime") => btime = fields.next().and_then(|v| v.parse().ok()),
+            _ => {}
+        }
+    }
+    Some(StatCounters {
+        ctxt: ctxt?,
+        intr: intr?,
+        btime: btime?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_loadavg_reads_loads_and_tasks() {
+        let load = parse_loadavg("0.46 0.22 0.08 2/72 2127\n").unwrap();
+        assert_eq!(
+            load,
+            LoadAvg {
+                one: 0.46,
+                five: 0.22,
+                fifteen: 0.08,
+                running: 2,
+                total: 72,
+            }
+        );
+        assert!(parse_loadavg("0.46 0.22").is_none());
+    }
+
+    #[test]
+    fn parse_uptime_reads_first_field() {
+        assert_eq!(parse_uptime("358.87 283.76\n"), Some(358.87));
+        assert_eq!(parse_uptime(""), None);
+    }
+
+    #[test]
+    fn parse_stat_reads_counters() {
+        let text = "cpu  6222 0 962 28376 205 0 1 221 0 0\n\
+                    intr 36936 0 0 1\n\
+                    ctxt 84851\n\
+                    btime 1792394549\n\
+                    processes 2130\n";
+        assert_eq!(
+            parse_stat(text),
+            Some(StatCounters {
+                ctxt: 84851,
+                intr: 36936,
+                btime: 1792394549,
+            })
+        );
+        assert_eq!(parse_stat("ctxt 1\n"), None);
+    }
+}
@@ -0,0 +1,37 @@
+//! Fixtures shared by unit tests.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// A scratch directory under the system temp dir, removed on drop.
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    /// Create an empty `rustop-<name>-<pid>` directory, clearing any left
+    /// over from an earlier run. `name` must be unique across tests.
+    pub fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("rustop-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        Self(path)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+
+    /// Write `contents` to `rel`, creating parent directories as needed.
+    pub fn write(&self, rel: &str, contents: impl AsRef<[u8]>) {
+        let path = self.0.join(rel);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
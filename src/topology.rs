@@ -0,0 +1,247 @@
+//! CPU topology discovery from sysfs (sockets, NUMA nodes, cores and SMT siblings).
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Core flavour on hybrid CPUs; `Unknown` on homogeneous parts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoreKind {
+    Performance,
+    Efficiency,
+    Unknown,
+}
+
+impl CoreKind {
+    /// Short label shown next to a core (`P`/`E`), empty when not hybrid.
+    pub fn label(self) -> &'static str {
+        match self {
+            CoreKind::Performance => "P",
+            CoreKind::Efficiency => "E",
+            CoreKind::Unknown => "",
+        }
+    }
+}
+
+/// Placement of a single logical CPU.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CpuPlacement {
+    pub cpu: usize,
+    pub package: u32,
+    pub node: u32,
+    pub core: u32,
+    pub kind: CoreKind,
+}
+
+/// A physical core and the logical CPUs (SMT siblings) it exposes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoreGroup {
+    pub core: u32,
+    pub kind: CoreKind,
+    pub cpus: Vec<usize>,
+}
+
+/// Cores sharing a socket and NUMA node.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeGroup {
+    pub package: u32,
+    pub node: u32,
+    pub cores: Vec<CoreGroup>,
+}
+
+impl NodeGroup {
+    /// All logical CPUs in this group.
+    pub fn cpus(&self) -> impl Iterator<Item = usize> + '_ {
+        self.cores.iter().flat_map(|core| core.cpus.iter().copied())
+    }
+}
+
+/// Static CPU topology, read once at startup.
+#[derive(Debug, Clone, Default)]
+pub struct CpuTopology {
+    placements: Vec<CpuPlacement>,
+}
+
+impl CpuTopology {
+    /// Read the topology of the running system.
+    pub fn detect() -> Self {
+        Self::from_sysfs(Path::new("/sys/devices"))
+    }
+
+    /// Read the topology from a sysfs `devices` directory (used by tests with a fake tree).
+    pub fn from_sysfs(devices: &Path) -> Self {
+        let cpu_root = devices.join("system/cpu");
+        let online = read_trimmed(&cpu_root.join("online"))
+            .map(|list| parse_cpu_list(&list))
+            .unwrap_or_default();
+
+        let mut node_of = BTreeMap::new();
+        if let Ok(entries) = fs::read_dir(devices.join("system/node")) {
+            for entry in entries.flatten() {
+                let name = entry.file_name();
+                let Some(node) = name
+                    .to_str()
+                    .and_then(|n| n.strip_prefix("node"))
+                    .and_then(|n| n.parse::<u32>().ok())
+                else {
+                    continue;
+                };
+                if let Some(list) = read_trimmed(&entry.path().join("cpulist")) {
+                    for cpu in parse_cpu_list(&list) {
+                        node_of.insert(cpu, node);
+                    }
+                }
+            }
+        }
+
+        let p_cores = read_trimmed(&devices.join("cpu_core/cpus"))
+            .map(|list| parse_cpu_list(&list))
+            .unwrap_or_default();
+        let e_cores = read_trimmed(&devices.join("cpu_atom/cpus"))
+            .map(|list| parse_cpu_list(&list))
+            .unwrap_or_default();
+
+        let placements = online
+            .into_iter()
+            .filter_map(|cpu| {
+                let topology: PathBuf = cpu_root.join(format!("cpu{cpu}/topology"));
+                let package = read_u32(&topology.join("physical_package_id"))?;
+                let core = read_u32(&topology.join("core_id"))?;
+                let kind = if p_cores.contains(&cpu) {
+                    CoreKind::Performance
+                } else if e_cores.contains(&cpu) {
+                    CoreKind::Efficiency
+                } else {
+                    CoreKind::Unknown
+                };
+                Some(CpuPlacement {
+                    cpu,
+                    package,
+                    node: node_of.get(&cpu).copied().unwrap_or(0),
+                    core,
+                    kind,
+                })
+            })
+            .collect();
+
+        Self { placements }
+    }
+
+    /// Whether any topology information was found.
+    pub fn is_available(&self) -> bool {
+        !self.placements.is_empty()
+    }
+
+    /// Group CPUs by socket, then NUMA node, then physical core.
+    pub fn groups(&self) -> Vec<NodeGroup> {
+        let mut grouped: BTreeMap<(u32, u32), BTreeMap<u32, CoreGroup>> = BTreeMap::new();
+        for placement in &self.placements {
+            let cores = grouped
+                .entry((placement.package, placement.node))
+                .or_default();
+            let core = cores.entry(placement.core).or_insert_with(|| CoreGroup {
+                core: placement.core,
+                kind: placement.kind,
+                cpus: Vec::new(),
+            });
+            core.cpus.push(placement.cpu);
+        }
+
+        grouped
+            .into_iter()
+            .map(|((package, node), cores)| NodeGroup {
+                package,
+                node,
+                cores: cores.into_values().collect(),
+            })
+            .collect()
+    }
+}
+
+/// Parse a kernel CPU list such as `0-3,8,10-11`.
+pub fn parse_cpu_list(list: &str) -> Vec<usize> {
+    let mut cpus = Vec::new();
+    for part in list.trim().split(',').filter(|p| !p.is_empty()) {
+        match part.split_once('-') {
+            Some((start, end)) => {
+                if let (Ok(start), Ok(end)) = (start.parse::<usize>(), end.parse::<usize>()) {
+                    cpus.extend(start..=end);
+                }
+            }
+            None => {
+                if let Ok(cpu) = part.parse() {
+                    cpus.push(cpu);
+                }
+            }
+        }
+    }
+    cpus
+}
+
+fn read_trimmed(path: &Path) -> Option<String> {
+    fs::read_to_string(path).ok().map(|s| s.trim().to_string())
+}
+
+fn read_u32(path: &Path) -> Option<u32> {
+    read_trimmed(path)?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDir;
+
+    #[test]
+    fn parse_cpu_list_handles_ranges() {
+        assert_eq!(parse_cpu_list("0-3,8,10-11\n"), vec![0, 1, 2, 3, 8, 10, 11]);
+        assert_eq!(parse_cpu_list(""), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn from_sysfs_groups_hybrid_smt_cpus() {
+        let root = TempDir::new("topology");
+        root.write("system/cpu/online", "0-3\n");
+        // cpu0/cpu1 are SMT siblings on a P-core, cpu2/cpu3 are separate E-cores.
+        for (cpu, core) in [(0, 0), (1, 0), (2, 8), (3, 9)] {
+            root.write(
+                &format!("system/cpu/cpu{cpu}/topology/physical_package_id"),
+                "0\n",
+            );
+            root.write(
+                &format!("system/cpu/cpu{cpu}/topology/core_id"),
+                format!("{core}\n"),
+            );
+        }
+        root.write("system/node/node0/cpulist", "0-3\n");
+        root.write("cpu_core/cpus", "0-1\n");
+        root.write("cpu_atom/cpus", "2-3\n");
+
+        let topology = CpuTopology::from_sysfs(root.path());
+        let groups = topology.groups();
+
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].cpus().collect::<Vec<_>>(), vec![0, 1, 2, 3]);
+        assert_eq!(
+            groups[0].cores,
+            vec![
+                CoreGroup {
+                    core: 0,
+                    kind: CoreKind::Performance,
+                    cpus: vec![0, 1],
+                },
+                CoreGroup {
+                    core: 8,
+                    kind: CoreKind::Efficiency,
+                    cpus: vec![2],
+                },
+                CoreGroup {
+                    core: 9,
+                    kind: CoreKind::Efficiency,
+                    cpus: vec![3],
+                },
+            ]
+        );
+    }
+}
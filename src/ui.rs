@@ -1,70 +1,290 @@
 //! Rendering layer for the `rustop` TUI.
 
-use crate::{cpu::CpuMonitor, gpu::GpuMonitor, utilities};
+mod cgroup;
+mod docker;
+mod process;
+
+use std::{cell, collections::HashSet};
+
+use crate::{
+    cpu::CpuMonitor,
+    gpu::GpuMonitor,
+    memory::{MemInfo, MemoryMonitor},
+    monitors::Monitors,
+    pressure::{PressureMonitor, Resource},
+    process::{
+        anomaly::{Anomaly, AnomalyKind},
+        control::SchedulingEdit,
+        detail::ProcessDetail,
+        CoreOccupant, GroupBy, MemoryColumn, SortKey,
+    },
+    swap::SwapMonitor,
+    system::SystemMonitor,
+    units::{ByteRate, Bytes, DisplayUnits},
+    utilities,
+};
 use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout, Rect},
-    style::{Color, Style},
+    style::{Color, Modifier, Style},
     text::{Line, Span},
     widgets::{Block, Borders, Cell, Gauge, Paragraph, Row, Table, Wrap},
     Frame,
 };
 
+/// Most process warnings listed above the main screen.
+const MAX_WARNING_LINES: usize = 4;
+/// Narrowest pressure sparkline worth drawing; below this it is left out.
+const MIN_SPARKLINE_WIDTH: usize = 4;
+
+/// Top-level screens cycled with Tab.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Screen {
+    #[default]
+    Overview,
+    Processes,
+    Cgroups,
+    Containers,
+}
+
+impl Screen {
+    /// The screen after this one, wrapping around.
+    pub fn next(self) -> Self {
+        match self {
+            Screen::Overview => Screen::Processes,
+            Screen::Processes => Screen::Cgroups,
+            Screen::Cgroups => Screen::Containers,
+            Screen::Containers => Screen::Overview,
+        }
+    }
+}
+
+/// Display options and selection state driven from the keyboard.
+#[derive(Debug, Clone, Default)]
+pub struct ViewState {
+    /// Screen shown below the summary gauges.
+    pub screen: Screen,
+    /// Group the per-thread table by socket, NUMA node and core.
+    pub group_by_topology: bool,
+    /// Show per-thread history as a heatmap instead of bars.
+    pub heatmap: bool,
+    /// Whether the terminal advertises 24-bit color support.
+    pub truecolor: bool,
+    /// Show pressure for rustop's own cgroup instead of the whole system.
+    pub cgroup_pressure: bool,
+    /// Byte and temperature units used for display.
+    pub units: DisplayUnits,
+    /// Process highlighted in the process table.
+    pub selected_pid: Option<u32>,
+    /// Detail of the process opened with Enter, if any.
+    pub detail: Option<ProcessDetail>,
+    /// Whether the process shown in the detail pane has exited.
+    pub detail_exited: bool,
+    /// Vertical scroll offset of the detail pane.
+    pub detail_scroll: u16,
+    /// Largest useful `detail_scroll` at the last render's pane size.
+    pub detail_max_scroll: cell::Cell<u16>,
+    /// Memory figure shown in the process table.
+    pub memory_column: MemoryColumn,
+    /// Column the process table is ordered by.
+    pub sort: SortKey,
+    /// Merge processes by user, executable or cgroup.
+    pub group_by: GroupBy,
+    /// Show the selected process's threads below its row.
+    pub expanded: bool,
+    /// Highlight the cores the selected process's threads run on in the CPU table.
+    pub highlight_cores: bool,
+    /// List the top processes last scheduled on each core in the CPU table.
+    pub occupancy: bool,
+    /// Show the process start/exit log below the process table.
+    pub event_log: bool,
+    /// Lines scrolled down in the event log.
+    pub event_scroll: u16,
+    /// Open nice/I/O priority/affinity editor, if any.
+    pub editor: Option<SchedulingEdit>,
+    /// One-line notice shown on the process table, e.g. why the editor could not open.
+    pub notice: Option<String>,
+    /// Why the last InfluxDB/StatsD push failed, cleared by the next success.
+    pub push_error: Option<String>,
+    /// cgroup path highlighted in the cgroup tree.
+    pub cgroup_selected: Option<String>,
+    /// cgroup paths whose children are listed in the cgroup tree.
+    pub cgroup_expanded: HashSet<String>,
+}
+
+/// Whether the terminal advertises truecolor via `COLORTERM`.
+pub fn supports_truecolor() -> bool {
+    std::env::var("COLORTERM")
+        .map(|value| value.contains("truecolor") || value.contains("24bit"))
+        .unwrap_or(false)
+}
+
 /// Draw the entire UI frame.
-pub fn draw(frame: &mut Frame<'_>, cpu: &CpuMonitor, gpu: &GpuMonitor) {
+pub fn draw(frame: &mut Frame<'_>, monitors: &Monitors, view: &ViewState) {
+    let cpu = &monitors.cpu;
+    let gpu = &monitors.gpu;
     let banner_text = utilities::banner_text();
     let banner_height = banner_text.lines().count() as u16;
+    let anomalies = monitors.process.anomalies().anomalies();
+    let warnings_height = if anomalies.is_empty() {
+        0
+    } else {
+        anomalies.len().min(MAX_WARNING_LINES) as u16 + 2
+    };
     let vertical = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
             Constraint::Length(banner_height.saturating_add(2)),
-            Constraint::Length(6),
+            Constraint::Length(8),
+            Constraint::Length(warnings_height),
             Constraint::Min(5),
             Constraint::Length(2),
         ])
         .split(frame.size());
 
+    let mut banner_block = Block::default().borders(Borders::ALL);
+    if let Some(container) = cpu.container() {
+        banner_block = banner_block.title(Span::styled(
+            container.summary(view.units.bytes),
+            Style::default().fg(Color::Yellow),
+        ));
+    }
     let banner = Paragraph::new(banner_text)
-        .block(Block::default().borders(Borders::ALL))
+        .block(banner_block)
         .wrap(Wrap { trim: true });
     frame.render_widget(banner, vertical[0]);
 
     let stats_chunks = Layout::default()
         .direction(Direction::Horizontal)
         .constraints([
-            Constraint::Percentage(34),
-            Constraint::Percentage(33),
-            Constraint::Percentage(33),
+            Constraint::Percentage(18),
+            Constraint::Percentage(18),
+            Constraint::Percentage(18),
+            Constraint::Percentage(24),
+            Constraint::Percentage(22),
         ])
         .split(vertical[1]);
 
     render_cpu_gauge(frame, stats_chunks[0], cpu);
-    render_memory_gauge(frame, stats_chunks[1], cpu);
-    render_info_panel(frame, stats_chunks[2], cpu, gpu);
+    render_memory_gauge(frame, stats_chunks[1], cpu, view.units);
+    render_swap_panel(frame, stats_chunks[2], cpu, &monitors.swap, view.units);
+    render_pressure_panel(frame, stats_chunks[3], &monitors.pressure, view);
+    render_info_panel(frame, stats_chunks[4], cpu, gpu, &monitors.system);
+
+    if !anomalies.is_empty() {
+        render_warnings(frame, vertical[2], anomalies, view.units);
+    }
+
+    match view.screen {
+        Screen::Overview => render_overview(frame, vertical[3], monitors, view),
+        Screen::Processes => process::render_processes(frame, vertical[3], monitors, view),
+        Screen::Cgroups => cgroup::render_cgroups(frame, vertical[3], monitors, view),
+        Screen::Containers => docker::render_containers(frame, vertical[3], monitors, view),
+    }
+
+    let mut status = vec![Line::from(format!(
+        "{}  {}",
+        utilities::QUIT_INSTRUCTIONS,
+        utilities::KEY_HINTS
+    ))];
+    if let Some(error) = &view.push_error {
+        status.push(Line::styled(
+            format!("push failed: {error}"),
+            Style::default().fg(Color::Red),
+        ));
+    }
+    let instructions = Paragraph::new(status)
+        .style(Style::default().fg(Color::Gray))
+        .alignment(Alignment::Center);
+    frame.render_widget(instructions, vertical[4]);
+}
 
+/// Zombies, long uninterruptible sleeps and suspected leaks, one per line.
+fn render_warnings(frame: &mut Frame<'_>, area: Rect, anomalies: &[Anomaly], units: DisplayUnits) {
+    let lines: Vec<Line> = anomalies
+        .iter()
+        .map(|anomaly| {
+            let (label, detail) = match anomaly.kind {
+                AnomalyKind::Zombie { ppid } => ("zombie", format!("not reaped by {ppid}")),
+                AnomalyKind::Blocked { secs } => (
+                    "D state",
+                    format!("for {}", utilities::format_duration(secs)),
+                ),
+                AnomalyKind::RssGrowth { from, to } => (
+                    "RSS growth",
+                    format!(
+                        "{} → {}",
+                        from.display(units.bytes),
+                        to.display(units.bytes)
+                    ),
+                ),
+                AnomalyKind::FdGrowth { from, to } => ("fd growth", format!("{from} → {to} fds")),
+            };
+            let mut text = format!(
+                "{label:<10} {:>7} {:<16} {detail}",
+                anomaly.pid, anomaly.name
+            );
+            if let Some(wchan) = &anomaly.wchan {
+                text.push_str(&format!("  wchan {wchan}"));
+            }
+            Line::from(Span::styled(text, Style::default().fg(Color::Yellow)))
+        })
+        .collect();
+    let title = if anomalies.len() > MAX_WARNING_LINES {
+        format!(
+            "Warnings ({}, showing {MAX_WARNING_LINES})",
+            anomalies.len()
+        )
+    } else {
+        format!("Warnings ({})", anomalies.len())
+    };
+    let paragraph = Paragraph::new(lines).block(
+        Block::default()
+            .title(Span::styled(title, Style::default().fg(Color::Yellow)))
+            .borders(Borders::ALL),
+    );
+    frame.render_widget(paragraph, area);
+}
+
+fn render_overview(frame: &mut Frame<'_>, area: Rect, monitors: &Monitors, view: &ViewState) {
+    let cpu = &monitors.cpu;
     let body_chunks = Layout::default()
         .direction(Direction::Horizontal)
         .constraints([Constraint::Percentage(67), Constraint::Percentage(33)])
-        .split(vertical[2]);
+        .split(area);
 
-    render_cpu_table(frame, body_chunks[0], cpu);
-    render_gpu_panel(frame, body_chunks[1], gpu);
+    if view.occupancy {
+        render_cpu_table(frame, body_chunks[0], monitors, view);
+    } else if view.heatmap {
+        render_cpu_heatmap(frame, body_chunks[0], cpu, view);
+    } else if view.group_by_topology && cpu.topology().is_available() {
+        render_cpu_topology_table(frame, body_chunks[0], cpu);
+    } else {
+        render_cpu_table(frame, body_chunks[0], monitors, view);
+    }
 
-    let instructions = Paragraph::new(utilities::QUIT_INSTRUCTIONS)
-        .style(Style::default().fg(Color::Gray))
-        .alignment(Alignment::Center);
-    frame.render_widget(instructions, vertical[3]);
+    let side_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(9), Constraint::Min(3)])
+        .split(body_chunks[1]);
+
+    render_memory_panel(frame, side_chunks[0], &monitors.memory, view.units);
+    render_gpu_panel(frame, side_chunks[1], monitors, view.units);
 }
 
 fn render_cpu_gauge(frame: &mut Frame<'_>, area: Rect, cpu: &CpuMonitor) {
     let avg_usage = cpu.avg().clamp(0.0, 100.0);
     let gauge_color = usage_color(avg_usage);
+    let mut title = match cpu.container().and_then(|c| c.cpu_quota()) {
+        Some(quota) => format!("CPU of {quota:.1} quota"),
+        None => "CPU Average".to_string(),
+    };
+    if let Some(frequency) = cpu.frequency() {
+        title.push_str(&format!(" @ {}", frequency.display()));
+    }
     let gauge = Gauge::default()
         .block(
             Block::default()
-                .title(Span::styled(
-                    "CPU Average",
-                    Style::default().fg(Color::Blue),
-                ))
+                .title(Span::styled(title, Style::default().fg(Color::Blue)))
                 .borders(Borders::ALL),
         )
         .gauge_style(Style::default().fg(gauge_color))
@@ -73,25 +293,30 @@ fn render_cpu_gauge(frame: &mut Frame<'_>, area: Rect, cpu: &CpuMonitor) {
     frame.render_widget(gauge, area);
 }
 
-fn render_memory_gauge(frame: &mut Frame<'_>, area: Rect, cpu: &CpuMonitor) {
-    let (used_kib, total_kib) = cpu.memory_usage();
+fn render_memory_gauge(frame: &mut Frame<'_>, area: Rect, cpu: &CpuMonitor, units: DisplayUnits) {
+    let (used, total) = cpu.memory_usage();
     let ratio = cpu.memory_ratio().clamp(0.0, 1.0);
     let percent = (ratio * 100.0) as f32;
     let gauge_color = usage_color(percent);
-    let label = if total_kib == 0 {
+    let title = if cpu.container().is_some_and(|c| c.memory_max().is_some()) {
+        "RAM Usage (memory.max)"
+    } else {
+        "RAM Usage"
+    };
+    let label = if total == Bytes(0) {
         "N/A".to_string()
     } else {
         format!(
             "{} / {}",
-            format_bytes_from_kib(used_kib),
-            format_bytes_from_kib(total_kib)
+            used.display(units.bytes),
+            total.display(units.bytes)
         )
     };
 
     let gauge = Gauge::default()
         .block(
             Block::default()
-                .title(Span::styled("RAM Usage", Style::default().fg(Color::Blue)))
+                .title(Span::styled(title, Style::default().fg(Color::Blue)))
                 .borders(Borders::ALL),
         )
         .gauge_style(Style::default().fg(gauge_color))
@@ -100,13 +325,319 @@ fn render_memory_gauge(frame: &mut Frame<'_>, area: Rect, cpu: &CpuMonitor) {
     frame.render_widget(gauge, area);
 }
 
-fn render_info_panel(frame: &mut Frame<'_>, area: Rect, cpu: &CpuMonitor, gpu: &GpuMonitor) {
-    let info = Paragraph::new(format!(
-        "Threads: {}\nGPUs: {}\nRefresh: 500ms",
-        cpu.thread_count(),
-        gpu.stats().len()
-    ))
-    .block(
+fn render_swap_panel(
+    frame: &mut Frame<'_>,
+    area: Rect,
+    cpu: &CpuMonitor,
+    swap: &SwapMonitor,
+    units: DisplayUnits,
+) {
+    let block = Block::default()
+        .title(Span::styled("Swap", Style::default().fg(Color::Blue)))
+        .borders(Borders::ALL);
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Min(0)])
+        .split(inner);
+
+    let (used, total) = cpu.swap_usage();
+    let ratio = cpu.swap_ratio().clamp(0.0, 1.0);
+    let label = if total == Bytes(0) {
+        "No swap".to_string()
+    } else {
+        format!(
+            "{} / {}",
+            used.display(units.bytes),
+            total.display(units.bytes)
+        )
+    };
+    let gauge = Gauge::default()
+        .gauge_style(Style::default().fg(usage_color((ratio * 100.0) as f32)))
+        .ratio(ratio)
+        .label(label);
+    frame.render_widget(gauge, chunks[0]);
+
+    let rate = |value: Option<ByteRate>| {
+        value
+            .map(|v| v.display(units.bytes))
+            .unwrap_or_else(|| "N/A".to_string())
+    };
+    let mut lines = vec![Line::from(format!(
+        "In {}  Out {}",
+        rate(swap.swap_in_rate()),
+        rate(swap.swap_out_rate())
+    ))];
+    for device in swap.zram() {
+        lines.push(Line::from(format!(
+            "{}: {} -> {} ({}), {} used of {}",
+            device.name,
+            device.orig_data_size.display(units.bytes),
+            device.compr_data_size.display(units.bytes),
+            device
+                .compression_ratio()
+                .map(|r| format!("{r:.1}x"))
+                .unwrap_or_else(|| "-".to_string()),
+            device.mem_used_total.display(units.bytes),
+            device.disk_size.display(units.bytes)
+        )));
+    }
+    if let Some(zswap) = swap.zswap() {
+        lines.push(Line::from(format!(
+            "zswap: {} pool, {} stored",
+            zswap.pool_size.display(units.bytes),
+            zswap.stored.display(units.bytes)
+        )));
+    }
+    frame.render_widget(Paragraph::new(lines), chunks[1]);
+}
+
+fn render_pressure_panel(
+    frame: &mut Frame<'_>,
+    area: Rect,
+    pressure: &PressureMonitor,
+    view: &ViewState,
+) {
+    let set = if view.cgroup_pressure {
+        pressure.cgroup().unwrap_or_else(|| pressure.system())
+    } else {
+        pressure.system()
+    };
+    let (windows, spark_width) = pressure_layout(usize::from(area.width.saturating_sub(2)));
+    let block = Block::default()
+        .title(Span::styled(
+            format!(
+                "Pressure {} ({})",
+                set.label(),
+                ["10s", "60s", "300s"][..windows].join("/")
+            ),
+            Style::default().fg(Color::Blue),
+        ))
+        .borders(Borders::ALL);
+
+    let mut lines = Vec::with_capacity(6);
+    for resource in Resource::ALL {
+        let track = set.track(resource);
+        let latest = track.latest();
+        for (kind, line, history) in [
+            ("some", latest.and_then(|p| p.some), track.some_history()),
+            ("full", latest.and_then(|p| p.full), track.full_history()),
+        ] {
+            let Some(line) = line else {
+                lines.push(Line::from(format!(
+                    "{:<3} {kind}   N/A",
+                    resource_label(resource)
+                )));
+                continue;
+            };
+            let averages: String = [line.avg10, line.avg60, line.avg300][..windows]
+                .iter()
+                .map(|avg| format!("{avg:5.2} "))
+                .collect();
+            let skip = history.len().saturating_sub(spark_width);
+            lines.push(Line::from(vec![
+                Span::raw(format!("{:<3} {kind} {averages}", resource_label(resource))),
+                Span::styled(
+                    sparkline(history.iter().skip(skip).copied()),
+                    Style::default().fg(usage_color(line.avg10)),
+                ),
+            ]));
+        }
+    }
+
+    frame.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+/// Averaging windows and sparkline width that fit `width` columns. Each row is
+/// `cpu some ` then one `12.34 ` per window; the sparkline goes first, then the
+/// 300s and 60s windows.
+fn pressure_layout(width: usize) -> (usize, usize) {
+    let windows = (width.saturating_sub(9) / 6).clamp(1, 3);
+    let spark_width = width.saturating_sub(9 + 6 * windows);
+    if windows < 3 || spark_width < MIN_SPARKLINE_WIDTH {
+        (windows, 0)
+    } else {
+        (windows, spark_width)
+    }
+}
+
+fn resource_label(resource: Resource) -> &'static str {
+    match resource {
+        Resource::Cpu => "cpu",
+        Resource::Memory => "mem",
+        Resource::Io => "io",
+    }
+}
+
+/// Render values as a text sparkline scaled to the largest value (at least 5%).
+fn sparkline(values: impl Iterator<Item = f32> + Clone) -> String {
+    const TICKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    let max = values.clone().fold(5.0_f32, f32::max);
+    values
+        .map(|value| {
+            let idx = ((value / max) * (TICKS.len() - 1) as f32).round() as usize;
+            TICKS[idx.min(TICKS.len() - 1)]
+        })
+        .collect()
+}
+
+fn render_memory_panel(
+    frame: &mut Frame<'_>,
+    area: Rect,
+    memory: &MemoryMonitor,
+    units: DisplayUnits,
+) {
+    let block = Block::default()
+        .title(Span::styled("Memory", Style::default().fg(Color::Blue)))
+        .borders(Borders::ALL);
+    let Some(info) = memory.info() else {
+        let empty = Paragraph::new("/proc/meminfo unavailable").block(block);
+        frame.render_widget(empty, area);
+        return;
+    };
+
+    let width = usize::from(block.inner(area).width);
+    let segments = memory_segments(info);
+    let mut bar = Vec::with_capacity(segments.len());
+    let mut filled = 0;
+    let mut cumulative = Bytes(0);
+    for (_, bytes, color) in &segments {
+        cumulative += *bytes;
+        let end = (cumulative.ratio_of(info.total) * width as f64).round() as usize;
+        let cells = end.saturating_sub(filled);
+        filled += cells;
+        bar.push(Span::styled("█".repeat(cells), Style::default().fg(*color)));
+    }
+
+    let entry = |label: &str, bytes: Bytes, color: Color| {
+        vec![
+            Span::styled(format!("{label} "), Style::default().fg(color)),
+            Span::raw(format!("{}  ", bytes.display(units.bytes))),
+        ]
+    };
+    let [used, shared, buffers, cache, slab, free] =
+        segments.map(|(label, bytes, color)| entry(label, bytes, color));
+
+    let hugepages = if info.hugepages_total == 0 {
+        "Huge: none".to_string()
+    } else {
+        format!(
+            "Huge: {}/{} free ({} pages)",
+            info.hugepages_free,
+            info.hugepages_total,
+            info.hugepage_size.display(units.bytes)
+        )
+    };
+
+    let lines = vec![
+        Line::from(bar),
+        Line::from([used, shared, buffers].concat()),
+        Line::from([cache, slab].concat()),
+        Line::from(format!(
+            "Slab: {} reclaimable / {} unreclaimable",
+            info.slab_reclaimable.display(units.bytes),
+            info.slab_unreclaimable.display(units.bytes)
+        )),
+        Line::from(
+            [
+                free,
+                vec![Span::raw(format!(
+                    "Avail {}",
+                    info.available.display(units.bytes)
+                ))],
+            ]
+            .concat(),
+        ),
+        Line::from(format!(
+            "Dirty {}  Writeback {}  {}",
+            info.dirty.display(units.bytes),
+            info.writeback.display(units.bytes),
+            hugepages
+        )),
+        Line::from(Span::styled(
+            format!(
+                "Committed {} / limit {}",
+                info.committed.display(units.bytes),
+                info.commit_limit.display(units.bytes)
+            ),
+            Style::default().fg(if info.committed > info.commit_limit {
+                Color::Red
+            } else {
+                Color::Reset
+            }),
+        )),
+    ];
+
+    frame.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+/// Stacked bar segments (label, bytes, color) that together add up to `MemTotal`.
+fn memory_segments(info: &MemInfo) -> [(&'static str, Bytes, Color); 6] {
+    [
+        ("Used", info.used(), Color::Green),
+        ("Shared", info.shmem, Color::Magenta),
+        ("Buffers", info.buffers, Color::Blue),
+        ("Cache", info.page_cache(), Color::Yellow),
+        ("Slab", info.slab(), Color::Cyan),
+        ("Free", info.free, Color::DarkGray),
+    ]
+}
+
+fn render_info_panel(
+    frame: &mut Frame<'_>,
+    area: Rect,
+    cpu: &CpuMonitor,
+    gpu: &GpuMonitor,
+    system: &SystemMonitor,
+) {
+    let na = || "N/A".to_string();
+    let load = system.load();
+    let lines = vec![
+        Line::from(format!(
+            "Threads: {}  GPUs: {}  Refresh: 500ms",
+            cpu.cpus().len(),
+            gpu.stats().len()
+        )),
+        Line::from(format!(
+            "Load: {}",
+            load.map(|l| format!("{:.2} {:.2} {:.2}", l.one, l.five, l.fifteen))
+                .unwrap_or_else(na)
+        )),
+        Line::from(format!(
+            "Tasks: {}",
+            load.map(|l| format!("{} running / {} total", l.running, l.total))
+                .unwrap_or_else(na)
+        )),
+        Line::from(format!(
+            "Uptime: {}",
+            system
+                .uptime_secs()
+                .map(|secs| utilities::format_duration(secs as u64))
+                .unwrap_or_else(na)
+        )),
+        Line::from(format!(
+            "Boot: {}",
+            system
+                .boot_time()
+                .map(utilities::format_epoch_utc)
+                .unwrap_or_else(na)
+        )),
+        Line::from(format!(
+            "Ctxt/s: {}  Intr/s: {}",
+            system
+                .ctxt_rate()
+                .map(utilities::format_rate)
+                .unwrap_or_else(na),
+            system
+                .intr_rate()
+                .map(utilities::format_rate)
+                .unwrap_or_else(na)
+        )),
+    ];
+
+    let info = Paragraph::new(lines).block(
         Block::default()
             .title(Span::styled("Info", Style::default().fg(Color::Blue)))
             .borders(Borders::ALL),
@@ -114,8 +645,10 @@ fn render_info_panel(frame: &mut Frame<'_>, area: Rect, cpu: &CpuMonitor, gpu: &
     frame.render_widget(info, area);
 }
 
-fn render_cpu_table(frame: &mut Frame<'_>, area: Rect, cpu: &CpuMonitor) {
-    let thread_count = cpu.thread_count();
+fn render_cpu_table(frame: &mut Frame<'_>, area: Rect, monitors: &Monitors, view: &ViewState) {
+    let cpu = &monitors.cpu;
+    let cpus = cpu.cpus();
+    let thread_count = cpus.len();
     if thread_count == 0 {
         let empty = Paragraph::new("No CPU data available").block(
             Block::default()
@@ -129,12 +662,24 @@ fn render_cpu_table(frame: &mut Frame<'_>, area: Rect, cpu: &CpuMonitor) {
         return;
     }
 
-    let min_column_width = 30_u16;
-    let mut columns = ((area.width / min_column_width) as usize).max(1);
-    columns = columns.min(thread_count.max(1));
-    if columns == 0 {
-        columns = 1;
-    }
+    // Cores last used by the selected process's threads.
+    let highlight = view
+        .highlight_cores
+        .then_some(view.selected_pid)
+        .flatten()
+        .map(|pid| (pid, monitors.process.occupied_cpus()));
+    let occupied = |idx: usize| {
+        highlight
+            .as_ref()
+            .is_some_and(|(_, cpus)| cpus.contains(&(idx as u32)))
+    };
+
+    let min_width = if view.occupancy {
+        OCCUPANCY_COLUMN_WIDTH
+    } else {
+        CPU_COLUMN_WIDTH
+    };
+    let columns = cpu_grid_columns(area.width, thread_count, min_width);
     let rows = thread_count.div_ceil(columns);
 
     let mut column_constraints = Vec::with_capacity(columns);
@@ -145,14 +690,31 @@ fn render_cpu_table(frame: &mut Frame<'_>, area: Rect, cpu: &CpuMonitor) {
 
     let cpu_rows = (0..rows).map(|row_idx| {
         let cells = (0..columns).map(|col_idx| {
-            let idx = row_idx * columns + col_idx;
-            if let Some(usage) = cpu.usages().get(idx) {
-                let color = usage_color(*usage);
-                let line = Line::from(vec![
-                    Span::raw(format!("CPU {:02}: {:5.1}% ", idx, usage)),
-                    Span::styled(make_bar_no_pct(*usage), Style::default().fg(color)),
-                ]);
-                Cell::from(line)
+            let cell = cpus.get(row_idx * columns + col_idx);
+            if let Some((idx, usage)) = cell.and_then(|&idx| Some((idx, cpu.usage(idx)?))) {
+                let color = usage_color(usage);
+                let label = format!("CPU {:02}: {:5.1}% ", idx, usage);
+                let label = if occupied(idx) {
+                    Span::styled(
+                        label,
+                        Style::default()
+                            .fg(Color::Black)
+                            .bg(Color::Cyan)
+                            .add_modifier(Modifier::BOLD),
+                    )
+                } else {
+                    Span::raw(label)
+                };
+                let mut spans = vec![label];
+                if view.occupancy {
+                    spans.extend(occupant_spans(monitors.process.occupants(idx as u32)));
+                } else {
+                    spans.push(Span::styled(
+                        make_bar_no_pct(usage),
+                        Style::default().fg(color),
+                    ));
+                }
+                Cell::from(Line::from(spans))
             } else {
                 Cell::from(String::new())
             }
@@ -160,21 +722,212 @@ fn render_cpu_table(frame: &mut Frame<'_>, area: Rect, cpu: &CpuMonitor) {
         Row::new(cells)
     });
 
+    let mut title = "Per-thread usage".to_string();
+    if view.occupancy {
+        title.push_str(" - top processes per core");
+    }
+    if let Some((pid, _)) = &highlight {
+        title.push_str(&format!(" - cores used by PID {pid}"));
+    }
     let cpu_table = Table::new(cpu_rows, column_constraints)
+        .block(
+            Block::default()
+                .title(Span::styled(title, Style::default().fg(Color::Blue)))
+                .borders(Borders::ALL),
+        )
+        .column_spacing(1);
+
+    frame.render_widget(cpu_table, area);
+}
+
+/// The processes last scheduled on a core, e.g. `python 92% · nginx 3%`.
+fn occupant_spans(occupants: &[CoreOccupant]) -> Vec<Span<'static>> {
+    if occupants.is_empty() {
+        return vec![Span::styled("idle", Style::default().fg(Color::DarkGray))];
+    }
+    let mut spans = Vec::with_capacity(occupants.len() * 2);
+    for (i, occupant) in occupants.iter().enumerate() {
+        if i > 0 {
+            spans.push(Span::styled(" · ", Style::default().fg(Color::DarkGray)));
+        }
+        spans.push(Span::styled(
+            format!("{} {:.0}%", occupant.name, occupant.cpu_percent),
+            Style::default().fg(usage_color(occupant.cpu_percent)),
+        ));
+    }
+    spans
+}
+
+/// Minimum width of one CPU cell in the per-thread table.
+const CPU_COLUMN_WIDTH: u16 = 30;
+/// Minimum width of one CPU cell when it also lists the core's occupants.
+const OCCUPANCY_COLUMN_WIDTH: u16 = 60;
+
+/// Number of CPU columns of at least `min_width` that fit in `width`, shared
+/// by every CPU-per-cell grid so they line up with the per-thread table.
+fn cpu_grid_columns(width: u16, thread_count: usize, min_width: u16) -> usize {
+    ((width / min_width.max(1)) as usize).clamp(1, thread_count.max(1))
+}
+
+fn render_cpu_topology_table(frame: &mut Frame<'_>, area: Rect, cpu: &CpuMonitor) {
+    // Drop threads outside the container's cpuset, and cores left empty.
+    let groups: Vec<_> = cpu
+        .topology()
+        .groups()
+        .into_iter()
+        .filter_map(|mut group| {
+            group.cores.retain_mut(|core| {
+                core.cpus.retain(|idx| cpu.cpus().contains(idx));
+                !core.cpus.is_empty()
+            });
+            (!group.cores.is_empty()).then_some(group)
+        })
+        .collect();
+    let max_siblings = groups
+        .iter()
+        .flat_map(|group| group.cores.iter().map(|core| core.cpus.len()))
+        .max()
+        .unwrap_or(1)
+        .max(1);
+
+    let header_style = Style::default().fg(Color::Cyan);
+    let mut rows = Vec::new();
+    for group in &groups {
+        let group_avg = average_usage(|id| cpu.usage(id), group.cpus());
+        // Spread the subtotal over the columns so the narrow first one does not clip it.
+        let mut cells = Vec::with_capacity(max_siblings + 2);
+        cells.push(Cell::from(format!("Socket {}", group.package)));
+        cells.push(Cell::from(format!(
+            "NUMA node {}: {} cores",
+            group.node,
+            group.cores.len()
+        )));
+        cells.extend((1..max_siblings).map(|_| Cell::from(String::new())));
+        cells.push(Cell::from(format!("avg {:5.1}%", group_avg)));
+        rows.push(Row::new(cells).style(header_style));
+
+        for core in &group.cores {
+            let mut cells = Vec::with_capacity(max_siblings + 2);
+            cells.push(Cell::from(format!(
+                "Core {:3} {}",
+                core.core,
+                core.kind.label()
+            )));
+            for slot in 0..max_siblings {
+                let cell = core
+                    .cpus
+                    .get(slot)
+                    .and_then(|&idx| cpu.usage(idx).map(|usage| (idx, usage)))
+                    .map(|(idx, usage)| {
+                        Cell::from(Line::from(vec![
+                            Span::raw(format!("CPU {:02}: {:5.1}% ", idx, usage)),
+                            Span::styled(
+                                make_bar_no_pct(usage),
+                                Style::default().fg(usage_color(usage)),
+                            ),
+                        ]))
+                    })
+                    .unwrap_or_else(|| Cell::from(String::new()));
+                cells.push(cell);
+            }
+            let core_avg = average_usage(|id| cpu.usage(id), core.cpus.iter().copied());
+            cells.push(Cell::from(Span::styled(
+                format!("avg {:5.1}%", core_avg),
+                Style::default().fg(usage_color(core_avg)),
+            )));
+            rows.push(Row::new(cells));
+        }
+    }
+
+    let mut column_constraints = Vec::with_capacity(max_siblings + 2);
+    column_constraints.push(Constraint::Length(10));
+    column_constraints.extend(std::iter::repeat_n(Constraint::Length(36), max_siblings));
+    column_constraints.push(Constraint::Length(10));
+
+    let table = Table::new(rows, column_constraints)
         .block(
             Block::default()
                 .title(Span::styled(
-                    "Per-thread usage",
+                    "Per-thread usage (by topology)",
                     Style::default().fg(Color::Blue),
                 ))
                 .borders(Borders::ALL),
         )
         .column_spacing(1);
 
-    frame.render_widget(cpu_table, area);
+    frame.render_widget(table, area);
+}
+
+fn render_cpu_heatmap(frame: &mut Frame<'_>, area: Rect, cpu: &CpuMonitor, view: &ViewState) {
+    let order: Vec<usize> = if view.group_by_topology && cpu.topology().is_available() {
+        cpu.topology()
+            .groups()
+            .iter()
+            .flat_map(|group| group.cpus().collect::<Vec<_>>())
+            .filter(|idx| cpu.cpus().contains(idx))
+            .collect()
+    } else {
+        cpu.cpus().to_vec()
+    };
+
+    let block = Block::default().borders(Borders::ALL);
+    let inner = block.inner(area);
+    let rows_available = usize::from(inner.height);
+    // Pack two threads per row with half blocks when one per row does not fit.
+    let packed = order.len() > rows_available;
+    let threads_per_row = if packed { 2 } else { 1 };
+    let shown = order.len().min(rows_available * threads_per_row);
+
+    let label_width = if packed { 8 } else { 7 };
+    let samples = usize::from(inner.width).saturating_sub(label_width);
+    let heat = |idx: usize, col: usize| -> Option<f32> {
+        let history = cpu.history(idx)?;
+        let offset = samples.checked_sub(history.len());
+        match offset {
+            Some(offset) if col < offset => None,
+            Some(offset) => history.get(col - offset).copied(),
+            None => history.get(history.len() - samples + col).copied(),
+        }
+    };
+    let empty = Color::Reset;
+
+    let lines: Vec<Line> = order[..shown]
+        .chunks(threads_per_row)
+        .map(|pair| {
+            let mut spans = Vec::with_capacity(samples + 1);
+            if let [top, bottom] = pair {
+                let label = format!("{top:>3}/{bottom:<3}");
+                spans.push(Span::raw(format!("{label:<label_width$}")));
+                for col in 0..samples {
+                    let fg = heat(*top, col).map_or(empty, |u| heat_color(u, view.truecolor));
+                    let bg = heat(*bottom, col).map_or(empty, |u| heat_color(u, view.truecolor));
+                    spans.push(Span::styled("▀", Style::default().fg(fg).bg(bg)));
+                }
+            } else {
+                let idx = pair[0];
+                // Pad to the packed width so an odd last thread lines up with the pairs.
+                let label = format!("CPU{idx:>3}");
+                spans.push(Span::raw(format!("{label:<label_width$}")));
+                for col in 0..samples {
+                    let bg = heat(idx, col).map_or(empty, |u| heat_color(u, view.truecolor));
+                    spans.push(Span::styled(" ", Style::default().bg(bg)));
+                }
+            }
+            Line::from(spans)
+        })
+        .collect();
+
+    let mut title = String::from("Per-thread heatmap (newest on the right)");
+    if shown < order.len() {
+        title.push_str(&format!(" - showing {} of {}", shown, order.len()));
+    }
+    let heatmap = Paragraph::new(lines)
+        .block(block.title(Span::styled(title, Style::default().fg(Color::Blue))));
+    frame.render_widget(heatmap, area);
 }
 
-fn render_gpu_panel(frame: &mut Frame<'_>, area: Rect, gpu: &GpuMonitor) {
+fn render_gpu_panel(frame: &mut Frame<'_>, area: Rect, monitors: &Monitors, units: DisplayUnits) {
+    let gpu = &monitors.gpu;
     let panel = Block::default()
         .title(Span::styled("GPU Usage", Style::default().fg(Color::Blue)))
         .borders(Borders::ALL);
@@ -201,11 +954,44 @@ fn render_gpu_panel(frame: &mut Frame<'_>, area: Rect, gpu: &GpuMonitor) {
         .split(inner);
 
     for (chunk, gpu_stat) in cards.iter().zip(stats.iter()) {
-        render_gpu_card(frame, *chunk, gpu_stat);
+        let pods = if monitors.kube.is_active() {
+            gpu_pods(gpu_stat, monitors)
+        } else {
+            Vec::new()
+        };
+        render_gpu_card(frame, *chunk, gpu_stat, &pods, units);
     }
 }
 
-fn render_gpu_card(frame: &mut Frame<'_>, area: Rect, gpu: &crate::gpu::GpuStats) {
+/// GPU memory per pod on one device, largest first.
+fn gpu_pods(gpu: &crate::gpu::GpuStats, monitors: &Monitors) -> Vec<(String, Bytes)> {
+    let mut pods: Vec<(String, Bytes)> = Vec::new();
+    for &(pid, bytes) in &gpu.processes {
+        let cgroup = monitors
+            .process
+            .processes()
+            .iter()
+            .find(|p| p.pid == pid)
+            .and_then(|p| p.cgroup.as_deref());
+        let Some(label) = monitors.kube.pod_label(cgroup) else {
+            continue;
+        };
+        match pods.iter_mut().find(|(name, _)| *name == label) {
+            Some((_, total)) => *total += bytes,
+            None => pods.push((label, bytes)),
+        }
+    }
+    pods.sort_by_key(|(_, bytes)| std::cmp::Reverse(*bytes));
+    pods
+}
+
+fn render_gpu_card(
+    frame: &mut Frame<'_>,
+    area: Rect,
+    gpu: &crate::gpu::GpuStats,
+    pods: &[(String, Bytes)],
+    units: DisplayUnits,
+) {
     let load_pct = gpu.utilization.clamp(0.0, 100.0);
     let load_line = Line::from(vec![
         Span::raw(format!("Use: {:3.0}% ", load_pct)),
@@ -215,14 +1001,14 @@ fn render_gpu_card(frame: &mut Frame<'_>, area: Rect, gpu: &crate::gpu::GpuStats
         ),
     ]);
 
-    let vram_line = if gpu.memory_total == 0 {
+    let vram_line = if gpu.memory_total == Bytes(0) {
         Line::from("Ram: N/A")
     } else {
-        let ratio = gpu.memory_used as f64 / gpu.memory_total as f64;
+        let ratio = gpu.memory_used.ratio_of(gpu.memory_total);
         let pct = (ratio * 100.0) as f32;
         let bar = make_bar_no_pct(pct);
-        let used = format_bytes(gpu.memory_used);
-        let total = format_bytes(gpu.memory_total);
+        let used = gpu.memory_used.display(units.bytes);
+        let total = gpu.memory_total.display(units.bytes);
         Line::from(vec![
             Span::raw(format!("Ram: {:3.0}% ", pct)),
             Span::styled(bar, Style::default().fg(usage_color(pct))),
@@ -233,21 +1019,71 @@ fn render_gpu_card(frame: &mut Frame<'_>, area: Rect, gpu: &crate::gpu::GpuStats
     let temp_line = Line::from(format!(
         "Temp: {}",
         gpu.temperature
-            .map(|t| format!("{t}°C"))
+            .map(|t| t.display(units.temperature))
             .unwrap_or_else(|| "N/A".to_string())
     ));
 
-    let lines = vec![
+    let mut lines = vec![
         Line::from(format!("GPU {}: {}", gpu.index, gpu.name)),
         load_line,
         vram_line,
         temp_line,
     ];
+    if !pods.is_empty() {
+        let pods: Vec<String> = pods
+            .iter()
+            .map(|(pod, bytes)| format!("{pod} {}", bytes.display(units.bytes)))
+            .collect();
+        lines.push(Line::from(format!("Pods: {}", pods.join(", "))));
+    }
 
     let card = Paragraph::new(lines).block(Block::default().borders(Borders::ALL));
     frame.render_widget(card, area);
 }
 
+/// Mean of `usage` over `cpus`, skipping CPUs it has no reading for.
+fn average_usage(usage: impl Fn(usize) -> Option<f32>, cpus: impl Iterator<Item = usize>) -> f32 {
+    let (sum, count) = cpus
+        .filter_map(usage)
+        .fold((0.0_f32, 0_usize), |(sum, count), usage| {
+            (sum + usage, count + 1)
+        });
+    if count == 0 {
+        0.0
+    } else {
+        sum / count as f32
+    }
+}
+
+/// Shade a utilization sample for the heatmap, using 24-bit or 256-color output.
+fn heat_color(value: f32, truecolor: bool) -> Color {
+    const STOPS: [(f32, (f32, f32, f32)); 4] = [
+        (0.0, (24.0, 24.0, 48.0)),
+        (0.4, (0.0, 170.0, 0.0)),
+        (0.75, (230.0, 200.0, 0.0)),
+        (1.0, (230.0, 0.0, 0.0)),
+    ];
+    const PALETTE_256: [u8; 10] = [17, 22, 28, 34, 70, 142, 178, 208, 202, 196];
+
+    let t = (value / 100.0).clamp(0.0, 1.0);
+    if !truecolor {
+        let idx =
+            ((t * (PALETTE_256.len() - 1) as f32).round() as usize).min(PALETTE_256.len() - 1);
+        return Color::Indexed(PALETTE_256[idx]);
+    }
+
+    let upper = STOPS
+        .iter()
+        .position(|(stop, _)| t <= *stop)
+        .unwrap_or(STOPS.len() - 1)
+        .max(1);
+    let (t0, c0) = STOPS[upper - 1];
+    let (t1, c1) = STOPS[upper];
+    let f = (t - t0) / (t1 - t0);
+    let lerp = |a: f32, b: f32| (a + (b - a) * f).round() as u8;
+    Color::Rgb(lerp(c0.0, c1.0), lerp(c0.1, c1.1), lerp(c0.2, c1.2))
+}
+
 fn usage_color(value: f32) -> Color {
     match value {
         v if v < 40.0 => Color::Green,
@@ -257,26 +1093,6 @@ fn usage_color(value: f32) -> Color {
     }
 }
 
-fn format_bytes_from_kib(kib: u64) -> String {
-    let bytes = kib.saturating_mul(1024);
-    format_bytes(bytes)
-}
-
-fn format_bytes(bytes: u64) -> String {
-    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
-    let mut unit = 0;
-    let mut value = bytes as f64;
-    while value >= 1024.0 && unit < UNITS.len() - 1 {
-        value /= 1024.0;
-        unit += 1;
-    }
-    if unit == 0 {
-        format!("{:.0} {}", value, UNITS[unit])
-    } else {
-        format!("{:.1} {}", value, UNITS[unit])
-    }
-}
-
 fn make_bar_no_pct(pct: f32) -> String {
     let ratio = pct / 100.0;
     let total_blocks = 20usize;
@@ -302,24 +1118,70 @@ mod tests {
     }
 
     #[test]
-    fn make_bar_no_pct_shapes_bar() {
-        assert_eq!(make_bar_no_pct(0.0), "[--------------------]");
-        assert_eq!(make_bar_no_pct(50.0), "[██████████----------]");
-        assert_eq!(make_bar_no_pct(100.0), "[████████████████████]");
+    fn heat_color_spans_gradient() {
+        assert_eq!(heat_color(0.0, true), Color::Rgb(24, 24, 48));
+        assert_eq!(heat_color(40.0, true), Color::Rgb(0, 170, 0));
+        assert_eq!(heat_color(100.0, true), Color::Rgb(230, 0, 0));
+        assert_eq!(heat_color(150.0, true), Color::Rgb(230, 0, 0));
+        assert_eq!(heat_color(0.0, false), Color::Indexed(17));
+        assert_eq!(heat_color(100.0, false), Color::Indexed(196));
     }
 
     #[test]
-    fn format_bytes_handles_units() {
-        assert_eq!(format_bytes(512), "512 B");
-        assert_eq!(format_bytes(1024), "1.0 KB");
-        assert_eq!(format_bytes(1536), "1.5 KB");
-        assert_eq!(format_bytes(1024 * 1024), "1.0 MB");
+    fn memory_segments_cover_total() {
+        let info = MemInfo {
+            total: Bytes(100),
+            free: Bytes(10),
+            buffers: Bytes(5),
+            cached: Bytes(40),
+            shmem: Bytes(8),
+            slab_reclaimable: Bytes(6),
+            slab_unreclaimable: Bytes(4),
+            ..MemInfo::default()
+        };
+        let segments = memory_segments(&info);
+        assert_eq!(
+            segments.iter().map(|(_, bytes, _)| *bytes).sum::<Bytes>(),
+            Bytes(100)
+        );
+        assert_eq!(segments[0].1, Bytes(35));
+        assert_eq!(segments[3].1, Bytes(32));
     }
 
     #[test]
-    fn format_bytes_from_kib_converts() {
-        assert_eq!(format_bytes_from_kib(0), "0 B");
-        assert_eq!(format_bytes_from_kib(1), "1.0 KB");
-        assert_eq!(format_bytes_from_kib(1024), "1.0 MB");
+    fn sparkline_scales_to_max() {
+        assert_eq!(sparkline([0.0, 10.0, 20.0].into_iter()), "▁▅█");
+        assert_eq!(sparkline([0.0, 5.0].into_iter()), "▁█");
+        assert_eq!(sparkline([1.0].into_iter()), "▂");
+        assert_eq!(sparkline(std::iter::empty()), "");
+    }
+
+    #[test]
+    fn pressure_layout_drops_sparkline_then_windows() {
+        assert_eq!(pressure_layout(40), (3, 13));
+        assert_eq!(pressure_layout(30), (3, 0));
+        assert_eq!(pressure_layout(26), (2, 0));
+        assert_eq!(pressure_layout(10), (1, 0));
+    }
+
+    #[test]
+    fn average_usage_ignores_missing_cpus() {
+        // CPU 1 is offline, so ids 0, 2 and 3 are the first three readings.
+        let usage = |id| match id {
+            0 => Some(10.0),
+            2 => Some(30.0),
+            3 => Some(50.0),
+            _ => None,
+        };
+        assert_eq!(average_usage(usage, [0, 2].into_iter()), 20.0);
+        assert_eq!(average_usage(usage, [1, 3].into_iter()), 50.0);
+        assert_eq!(average_usage(usage, std::iter::empty()), 0.0);
+    }
+
+    #[test]
+    fn make_bar_no_pct_shapes_bar() {
+        assert_eq!(make_bar_no_pct(0.0), "[--------------------]");
+        assert_eq!(make_bar_no_pct(50.0), "[██████████----------]");
+        assert_eq!(make_bar_no_pct(100.0), "[████████████████████]");
     }
 }
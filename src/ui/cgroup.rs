@@ -0,0 +1,144 @@
+//! Expandable cgroup v2 tree with per-group resource usage.
+
+use ratatui::{
+    layout::{Constraint, Rect},
+    style::{Color, Modifier, Style},
+    text::Span,
+    widgets::{Block, Borders, Cell, Paragraph, Row, Table, TableState},
+    Frame,
+};
+
+use super::{usage_color, ViewState};
+use crate::{
+    cgroup::tree::{self, CgroupNode},
+    monitors::Monitors,
+    pressure::Pressure,
+    units::{ByteRate, Bytes, DisplayUnits},
+};
+
+/// Render the cgroup screen: one row per visible group, children indented.
+pub fn render_cgroups(frame: &mut Frame<'_>, area: Rect, monitors: &Monitors, view: &ViewState) {
+    let cgroups = &monitors.cgroups;
+    let Some(root) = cgroups.root() else {
+        let paragraph = Paragraph::new("No cgroup v2 hierarchy is mounted.").block(
+            Block::default()
+                .title(Span::styled("cgroups", Style::default().fg(Color::Blue)))
+                .borders(Borders::ALL),
+        );
+        frame.render_widget(paragraph, area);
+        return;
+    };
+
+    let nodes = tree::visible(cgroups.nodes(), &view.cgroup_expanded);
+    let header = Row::new(vec![
+        "CGROUP",
+        "CPU%",
+        "MEMORY",
+        "MAX",
+        "READ",
+        "WRITE",
+        "PIDS",
+        "PSI cpu/mem/io",
+    ])
+    .style(Style::default().fg(Color::Cyan));
+    let rows: Vec<Row> = nodes
+        .iter()
+        .map(|node| {
+            let expanded = node.depth == 0 || view.cgroup_expanded.contains(&node.path);
+            cgroup_row(node, expanded, view.units)
+        })
+        .collect();
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Min(30),
+            Constraint::Length(6),
+            Constraint::Length(10),
+            Constraint::Length(10),
+            Constraint::Length(12),
+            Constraint::Length(12),
+            Constraint::Length(5),
+            Constraint::Length(17),
+        ],
+    )
+    .header(header)
+    .block(
+        Block::default()
+            .title(Span::styled(
+                format!("cgroups ({}, {})", cgroups.nodes().len(), root.display()),
+                Style::default().fg(Color::Blue),
+            ))
+            .borders(Borders::ALL),
+    )
+    .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+    .column_spacing(1);
+
+    let selected = view
+        .cgroup_selected
+        .as_ref()
+        .and_then(|path| nodes.iter().position(|node| &node.path == path));
+    let mut state = TableState::default().with_selected(selected);
+    frame.render_stateful_widget(table, area, &mut state);
+}
+
+fn cgroup_row(node: &CgroupNode, expanded: bool, units: DisplayUnits) -> Row<'static> {
+    let marker = match (node.has_children, expanded) {
+        (false, _) => " ",
+        (true, true) => "▾",
+        (true, false) => "▸",
+    };
+    let bytes = |value: Option<Bytes>| {
+        value
+            .map(|bytes| bytes.display(units.bytes))
+            .unwrap_or_else(|| "-".to_string())
+    };
+    let rate = |value: Option<ByteRate>| {
+        value
+            .map(|rate| rate.display(units.bytes))
+            .unwrap_or_else(|| "-".to_string())
+    };
+    let memory_style = match (node.memory_current, node.memory_max) {
+        (Some(current), Some(max)) if max.0 > 0 => {
+            Style::default().fg(usage_color(current.0 as f32 / max.0 as f32 * 100.0))
+        }
+        _ => Style::default(),
+    };
+    let cpu = node
+        .cpu_percent
+        .map(|cpu| Span::styled(format!("{cpu:5.1}"), Style::default().fg(usage_color(cpu))))
+        .unwrap_or_else(|| Span::raw("-"));
+
+    Row::new(vec![
+        Cell::from(format!("{}{marker} {}", "  ".repeat(node.depth), node.name)),
+        Cell::from(cpu),
+        Cell::from(bytes(node.memory_current)).style(memory_style),
+        Cell::from(match (node.memory_current, node.memory_max) {
+            (None, _) => "-".to_string(),
+            (Some(_), Some(max)) => max.display(units.bytes),
+            (Some(_), None) => "max".to_string(),
+        }),
+        Cell::from(rate(node.read_rate)),
+        Cell::from(rate(node.write_rate)),
+        Cell::from(
+            node.pids
+                .map(|pids| pids.to_string())
+                .unwrap_or_else(|| "-".to_string()),
+        ),
+        Cell::from(
+            node.pressure
+                .iter()
+                .map(|pressure| psi_some(*pressure))
+                .collect::<Vec<_>>()
+                .join(" "),
+        ),
+    ])
+}
+
+/// `some` avg10 of one pressure file, `-` when unavailable.
+fn psi_some(pressure: Option<Pressure>) -> String {
+    pressure
+        .and_then(|p| p.some)
+        .map(|line| format!("{:.1}", line.avg10))
+        .unwrap_or_else(|| "-".to_string())
+}
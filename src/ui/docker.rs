@@ -0,0 +1,119 @@
+//! Running Docker/Podman containers with usage and GPU assignment.
+
+use ratatui::{
+    layout::{Constraint, Rect},
+    style::{Color, Style},
+    text::Span,
+    widgets::{Block, Borders, Cell, Paragraph, Row, Table},
+    Frame,
+};
+
+use super::{usage_color, ViewState};
+use crate::{docker, monitors::Monitors};
+
+/// Render the containers screen, or why the engine could not be reached.
+pub fn render_containers(frame: &mut Frame<'_>, area: Rect, monitors: &Monitors, view: &ViewState) {
+    let engine = &monitors.docker;
+    let block = |title: String| {
+        Block::default()
+            .title(Span::styled(title, Style::default().fg(Color::Blue)))
+            .borders(Borders::ALL)
+    };
+    let Some(socket) = engine.socket() else {
+        let paragraph = Paragraph::new(
+            "No Docker or Podman socket found (checked DOCKER_HOST, /var/run/docker.sock and the Podman sockets).",
+        )
+        .block(block("Containers".to_string()));
+        frame.render_widget(paragraph, area);
+        return;
+    };
+    if let Some(error) = engine.error() {
+        let paragraph = Paragraph::new(Span::styled(
+            format!("Cannot query {}: {error}", socket.display()),
+            Style::default().fg(Color::Red),
+        ))
+        .block(block("Containers".to_string()));
+        frame.render_widget(paragraph, area);
+        return;
+    }
+
+    let units = view.units.bytes;
+    let gpus = monitors.gpu.stats();
+    let header = Row::new(vec![
+        "NAME", "IMAGE", "CPU%", "MEMORY", "MAX", "NET", "GPU", "ID",
+    ])
+    .style(Style::default().fg(Color::Cyan));
+    let rows: Vec<Row> = engine
+        .containers()
+        .iter()
+        .map(|container| {
+            let cpu = container
+                .cpu_percent
+                .map(|cpu| {
+                    Span::styled(format!("{cpu:5.1}"), Style::default().fg(usage_color(cpu)))
+                })
+                .unwrap_or_else(|| Span::raw("-"));
+            let gpu = match container.gpu_spec.as_deref() {
+                None => "-".to_string(),
+                Some(spec) => {
+                    let indices = docker::gpu_indices(spec, gpus);
+                    if indices.is_empty() {
+                        spec.to_string()
+                    } else {
+                        indices
+                            .iter()
+                            .map(u32::to_string)
+                            .collect::<Vec<_>>()
+                            .join(",")
+                    }
+                }
+            };
+            Row::new(vec![
+                Cell::from(container.name.clone()),
+                Cell::from(container.image.clone()),
+                Cell::from(cpu),
+                Cell::from(
+                    container
+                        .memory
+                        .map(|bytes| bytes.display(units))
+                        .unwrap_or_else(|| "-".to_string()),
+                ),
+                Cell::from(match (container.memory, container.memory_max) {
+                    (None, _) => "-".to_string(),
+                    (Some(_), Some(max)) => max.display(units),
+                    (Some(_), None) => "max".to_string(),
+                }),
+                Cell::from(
+                    container
+                        .net_rate
+                        .map(|rate| rate.display(units))
+                        .unwrap_or_else(|| "-".to_string()),
+                ),
+                Cell::from(gpu),
+                Cell::from(container.id.chars().take(12).collect::<String>()),
+            ])
+        })
+        .collect();
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Min(16),
+            Constraint::Min(20),
+            Constraint::Length(6),
+            Constraint::Length(10),
+            Constraint::Length(10),
+            Constraint::Length(12),
+            Constraint::Length(8),
+            Constraint::Length(12),
+        ],
+    )
+    .header(header)
+    .block(block(format!(
+        "Containers ({}, {})",
+        engine.containers().len(),
+        socket.display()
+    )))
+    .column_spacing(1);
+    frame.render_widget(table, area);
+}
@@ -0,0 +1,659 @@
+//! Process table and per-process detail pane.
+
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Cell, Clear, Paragraph, Row, Table, TableState, Wrap},
+    Frame,
+};
+
+use super::{cpu_grid_columns, usage_color, ViewState, CPU_COLUMN_WIDTH};
+use crate::{
+    cpu::CpuMonitor,
+    monitors::Monitors,
+    process::{
+        self,
+        control::{EditField, SchedulingEdit},
+        detail::ProcessDetail,
+        lifecycle::{EventKind, ProcessEvent},
+        GroupBy, ProcessInfo, SortKey, ThreadInfo,
+    },
+    units::{ByteRate, Bytes, DisplayUnits},
+    utilities,
+};
+
+/// Render the Processes screen: the table plus, when open, the detail pane
+/// and event log.
+pub fn render_processes(frame: &mut Frame<'_>, area: Rect, monitors: &Monitors, view: &ViewState) {
+    let area = if view.event_log {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(5), Constraint::Length(10)])
+            .split(area);
+        render_event_log(frame, chunks[1], monitors, view);
+        chunks[0]
+    } else {
+        area
+    };
+
+    if view.group_by != GroupBy::None {
+        render_group_table(frame, area, monitors, view);
+        return;
+    }
+
+    match view.detail.as_ref() {
+        Some(detail) => {
+            let chunks = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Percentage(45), Constraint::Percentage(55)])
+                .split(area);
+            render_process_table(frame, chunks[0], monitors, view);
+            render_process_detail(frame, chunks[1], detail, view);
+        }
+        None => render_process_table(frame, area, monitors, view),
+    }
+
+    if let Some(edit) = view.editor.as_ref() {
+        render_scheduling_editor(frame, area, edit, &monitors.cpu);
+    }
+}
+
+fn render_process_table(frame: &mut Frame<'_>, area: Rect, monitors: &Monitors, view: &ViewState) {
+    let processes = process::sorted(monitors.process.processes(), view.sort, view.memory_column);
+    let column = view.memory_column;
+    let kube = &monitors.kube;
+    let mut headers = vec![
+        ("PID", Some(SortKey::Pid)),
+        ("USER", None),
+        ("S", None),
+        ("CPU%", Some(SortKey::Cpu)),
+        (column.label(), Some(SortKey::Memory)),
+        ("THR", None),
+        ("READ", Some(SortKey::DiskRead)),
+        ("WRITE", Some(SortKey::DiskWrite)),
+        ("NET", Some(SortKey::Network)),
+    ];
+    // Pod names only take room on Kubernetes nodes.
+    if kube.is_active() {
+        headers.push(("POD", None));
+    }
+    headers.push(("COMMAND", None));
+    let header = Row::new(headers.into_iter().map(|(label, key)| {
+        if key == Some(view.sort) {
+            Cell::from(format!("{label}▼")).style(Style::default().add_modifier(Modifier::BOLD))
+        } else {
+            Cell::from(label)
+        }
+    }))
+    .style(Style::default().fg(Color::Cyan));
+    let bytes = |value: Option<Bytes>| {
+        value
+            .map(|bytes| bytes.display(view.units.bytes))
+            .unwrap_or_else(|| "-".to_string())
+    };
+    let rate = |value: Option<ByteRate>| {
+        value
+            .map(|rate| rate.display(view.units.bytes))
+            .unwrap_or_else(|| "-".to_string())
+    };
+    let process_row = |process: &ProcessInfo| {
+        let mut cells = vec![
+            Cell::from(process.pid.to_string()),
+            Cell::from(process.user.clone()),
+            Cell::from(process.state.to_string()),
+            Cell::from(Span::styled(
+                format!("{:5.1}", process.cpu_percent),
+                Style::default().fg(usage_color(process.cpu_percent)),
+            )),
+            Cell::from(bytes(column.value(process))),
+            Cell::from(process.threads.to_string()),
+            Cell::from(rate(process.read_rate)),
+            Cell::from(rate(process.write_rate)),
+            Cell::from(rate(process.net_rate)),
+        ];
+        if kube.is_active() {
+            cells.push(Cell::from(
+                kube.pod_label(process.cgroup.as_deref())
+                    .unwrap_or_else(|| "-".to_string()),
+            ));
+        }
+        cells.push(Cell::from(process.command.clone()));
+        Row::new(cells)
+    };
+
+    // Processes that just exited stay on top in red for a few refreshes.
+    let lifecycle = monitors.process.lifecycle();
+    let threads = monitors.process.threads();
+    let mut rows: Vec<Row> = lifecycle
+        .recently_exited()
+        .map(|process| process_row(process).style(Style::default().fg(Color::Red)))
+        .collect();
+    let exited_rows = rows.len();
+    for process in &processes {
+        let row = process_row(process);
+        rows.push(if lifecycle.is_new(process.pid) {
+            row.style(Style::default().fg(Color::Green))
+        } else {
+            row
+        });
+        if view.expanded && Some(process.pid) == view.selected_pid {
+            rows.extend(
+                threads
+                    .iter()
+                    .map(|thread| thread_row(thread, kube.is_active())),
+            );
+        }
+    }
+
+    let mut widths = vec![
+        Constraint::Length(7),
+        Constraint::Length(10),
+        Constraint::Length(1),
+        Constraint::Length(6),
+        Constraint::Length(10),
+        Constraint::Length(4),
+        Constraint::Length(12),
+        Constraint::Length(12),
+        Constraint::Length(12),
+    ];
+    if kube.is_active() {
+        widths.push(Constraint::Length(28));
+    }
+    widths.push(Constraint::Min(10));
+    let table = Table::new(rows, widths)
+        .header(header)
+        .block(
+            Block::default()
+                .title(Line::from(vec![
+                    Span::styled(
+                        format!("Processes ({})", processes.len()),
+                        Style::default().fg(Color::Blue),
+                    ),
+                    Span::styled(
+                        view.notice
+                            .as_ref()
+                            .map(|notice| format!(" {notice} "))
+                            .unwrap_or_default(),
+                        Style::default().fg(Color::Red),
+                    ),
+                ]))
+                .borders(Borders::ALL),
+        )
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+        .column_spacing(1);
+
+    let selected = view
+        .selected_pid
+        .and_then(|pid| processes.iter().position(|p| p.pid == pid))
+        .map(|index| index + exited_rows);
+    // Scroll so an expanded process's thread rows are visible too.
+    let visible_rows = usize::from(area.height.saturating_sub(3)).max(1);
+    let offset = match selected {
+        Some(index) if view.expanded => (index + threads.len() + 1)
+            .saturating_sub(visible_rows)
+            .min(index),
+        _ => 0,
+    };
+    let mut state = TableState::default()
+        .with_offset(offset)
+        .with_selected(selected);
+    frame.render_stateful_widget(table, area, &mut state);
+}
+
+fn render_process_detail(
+    frame: &mut Frame<'_>,
+    area: Rect,
+    detail: &ProcessDetail,
+    view: &ViewState,
+) {
+    let title = format!(
+        "PID {} - {}{}",
+        detail.pid,
+        detail.stat.comm,
+        if view.detail_exited { " (exited)" } else { "" }
+    );
+    let block = Block::default()
+        .title(Span::styled(title, Style::default().fg(Color::Blue)))
+        .borders(Borders::ALL);
+    let inner = block.inner(area);
+    let paragraph = Paragraph::new(detail_lines(detail, view.units)).wrap(Wrap { trim: false });
+    let max_scroll = wrapped_max_scroll(&paragraph, inner);
+    // Remembered so PageDown stops once the last wrapped row is in view.
+    view.detail_max_scroll.set(max_scroll);
+    frame.render_widget(block, area);
+    frame.render_widget(
+        paragraph.scroll((view.detail_scroll.min(max_scroll), 0)),
+        inner,
+    );
+}
+
+/// Scrollable log of process starts and exits, newest first.
+fn render_event_log(frame: &mut Frame<'_>, area: Rect, monitors: &Monitors, view: &ViewState) {
+    let lifecycle = monitors.process.lifecycle();
+    let lines: Vec<Line> = lifecycle
+        .events()
+        .iter()
+        .rev()
+        .map(|event| event_line(event, view.units))
+        .collect();
+    let source = if lifecycle.connector_active() {
+        "proc connector + scan"
+    } else {
+        "scan"
+    };
+    let paragraph = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .title(Span::styled(
+                    format!("Events ({}, {source})", lifecycle.events().len()),
+                    Style::default().fg(Color::Blue),
+                ))
+                .borders(Borders::ALL),
+        )
+        .scroll((view.event_scroll, 0));
+    frame.render_widget(paragraph, area);
+}
+
+fn event_line(event: &ProcessEvent, units: DisplayUnits) -> Line<'static> {
+    let (marker, color) = match event.kind {
+        EventKind::Started => ("+", Color::Green),
+        EventKind::Exited => ("-", Color::Red),
+        EventKind::ShortLived => ("±", Color::Yellow),
+    };
+    let mut text = format!(
+        "{} {marker} {:>7} {}",
+        utilities::format_clock_utc(event.time),
+        event.pid,
+        if event.command.is_empty() {
+            &event.name
+        } else {
+            &event.command
+        }
+    );
+    if let Some(runtime) = event.runtime_secs {
+        text.push_str(&format!("  ran {}", utilities::format_duration(runtime)));
+    }
+    if event.kind == EventKind::Exited {
+        text.push_str(&format!(
+            "  peak CPU {:.0}%  peak RSS {}",
+            event.peak_cpu,
+            event.peak_rss.display(units.bytes)
+        ));
+    }
+    Line::from(Span::styled(text, Style::default().fg(color)))
+}
+
+/// Process table merged by user, executable, cgroup or systemd unit.
+fn render_group_table(frame: &mut Frame<'_>, area: Rect, monitors: &Monitors, view: &ViewState) {
+    let groups = process::group_processes(
+        monitors.process.processes(),
+        view.group_by,
+        view.sort,
+        view.memory_column,
+        |pid| monitors.gpu.process_memory(pid),
+    );
+    let header = Row::new([
+        view.group_by.label(),
+        "PROCS",
+        "TASKS",
+        "CPU%",
+        view.memory_column.label(),
+        "READ/s",
+        "WRITE/s",
+        "GPU MEM",
+    ])
+    .style(Style::default().fg(Color::Cyan));
+    let rate = |value: Option<ByteRate>| {
+        value
+            .map(|rate| rate.display(view.units.bytes))
+            .unwrap_or_else(|| "-".to_string())
+    };
+    let rows = groups.iter().map(|group| {
+        Row::new(vec![
+            Cell::from(group.key.clone()),
+            Cell::from(group.count.to_string()),
+            Cell::from(group.tasks.to_string()),
+            Cell::from(Span::styled(
+                format!("{:6.1}", group.cpu_percent),
+                Style::default().fg(usage_color(group.cpu_percent)),
+            )),
+            Cell::from(
+                group
+                    .memory
+                    .map(|bytes| bytes.display(view.units.bytes))
+                    .unwrap_or_else(|| "-".to_string()),
+            ),
+            Cell::from(rate(group.read_rate)),
+            Cell::from(rate(group.write_rate)),
+            Cell::from(if group.gpu_memory.0 > 0 {
+                group.gpu_memory.display(view.units.bytes)
+            } else {
+                "-".to_string()
+            }),
+        ])
+    });
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(40),
+            Constraint::Length(6),
+            Constraint::Length(6),
+            Constraint::Length(7),
+            Constraint::Length(11),
+            Constraint::Length(11),
+            Constraint::Length(11),
+            Constraint::Min(11),
+        ],
+    )
+    .header(header)
+    .block(
+        Block::default()
+            .title(Span::styled(
+                format!(
+                    "Processes by {} ({} groups)",
+                    view.group_by.label().to_lowercase(),
+                    groups.len()
+                ),
+                Style::default().fg(Color::Blue),
+            ))
+            .borders(Borders::ALL),
+    )
+    .column_spacing(1);
+    frame.render_widget(table, area);
+}
+
+/// An indented row for one thread of the expanded process.
+fn thread_row(thread: &ThreadInfo, pod_column: bool) -> Row<'static> {
+    let processor = thread
+        .processor
+        .map(|cpu| format!("CPU {cpu}"))
+        .unwrap_or_else(|| "CPU ?".to_string());
+    let mut cells = vec![
+        Cell::from(format!("└{}", thread.tid)),
+        Cell::from(String::new()),
+        Cell::from(thread.state.to_string()),
+        Cell::from(Span::styled(
+            format!("{:5.1}", thread.cpu_percent),
+            Style::default().fg(usage_color(thread.cpu_percent)),
+        )),
+        Cell::from(processor),
+        Cell::from(String::new()),
+        Cell::from(String::new()),
+        Cell::from(String::new()),
+        Cell::from(String::new()),
+    ];
+    if pod_column {
+        cells.push(Cell::from(String::new()));
+    }
+    cells.push(Cell::from(thread.name.clone()));
+    Row::new(cells).style(Style::default().fg(Color::Gray))
+}
+
+/// Popup editing nice, I/O priority and a CPU affinity grid laid out like
+/// the per-thread table.
+fn render_scheduling_editor(
+    frame: &mut Frame<'_>,
+    area: Rect,
+    edit: &SchedulingEdit,
+    monitor: &CpuMonitor,
+) {
+    let width = area.width.saturating_sub(4).max(1);
+    let columns = cpu_grid_columns(
+        width.saturating_sub(2),
+        edit.affinity.len(),
+        CPU_COLUMN_WIDTH,
+    );
+    let grid_rows = edit.affinity.len().div_ceil(columns) as u16;
+    let height = (grid_rows + 9).min(area.height);
+    let popup = Rect {
+        x: area.x + area.width.saturating_sub(width) / 2,
+        y: area.y + area.height.saturating_sub(height) / 2,
+        width,
+        height,
+    };
+    frame.render_widget(Clear, popup);
+
+    let block = Block::default()
+        .title(Span::styled(
+            format!("Scheduling: PID {} - {}", edit.pid, edit.name),
+            Style::default().fg(Color::Blue),
+        ))
+        .borders(Borders::ALL);
+    let inner = block.inner(popup);
+    frame.render_widget(block, popup);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(4),
+            Constraint::Min(1),
+            Constraint::Length(2),
+        ])
+        .split(inner);
+
+    let focus = |field: EditField| {
+        if edit.field == field {
+            Style::default().add_modifier(Modifier::REVERSED)
+        } else {
+            Style::default()
+        }
+    };
+    let fields = vec![
+        Line::from(vec![
+            Span::raw("Nice       "),
+            Span::styled(format!("< {:>3} >", edit.nice), focus(EditField::Nice)),
+        ]),
+        Line::from(vec![
+            Span::raw("I/O class  "),
+            Span::styled(
+                format!("< {} >", edit.io.class.label()),
+                focus(EditField::IoClass),
+            ),
+        ]),
+        Line::from(vec![
+            Span::raw("I/O level  "),
+            Span::styled(format!("< {} >", edit.io.level), focus(EditField::IoLevel)),
+        ]),
+        Line::from(Span::styled(
+            "CPU affinity",
+            Style::default().fg(Color::Cyan),
+        )),
+    ];
+    frame.render_widget(Paragraph::new(fields), chunks[0]);
+
+    let rows = (0..edit.affinity.len().div_ceil(columns)).map(|row_idx| {
+        Row::new((0..columns).map(|col_idx| {
+            let idx = row_idx * columns + col_idx;
+            let (Some(on), Some(&cpu)) = (edit.affinity.get(idx), edit.cpus.get(idx)) else {
+                return Cell::from(String::new());
+            };
+            let usage = monitor.usage(cpu).unwrap_or(0.0);
+            let style = if edit.field == EditField::Affinity && edit.cursor == idx {
+                Style::default().add_modifier(Modifier::REVERSED)
+            } else {
+                Style::default()
+            };
+            Cell::from(Line::from(vec![
+                Span::styled(
+                    format!("[{}] CPU {:02}", if *on { 'x' } else { ' ' }, cpu),
+                    style,
+                ),
+                Span::styled(
+                    format!(" {:5.1}%", usage),
+                    Style::default().fg(usage_color(usage)),
+                ),
+            ]))
+        }))
+    });
+    let constraints = vec![Constraint::Percentage((100 / columns as u16).max(1)); columns];
+    frame.render_widget(Table::new(rows, constraints).column_spacing(1), chunks[1]);
+
+    let (text, color) = match &edit.message {
+        Some(Ok(message)) => (message.clone(), Color::Green),
+        Some(Err(message)) => (message.clone(), Color::Red),
+        None => (
+            "Up/Down: field  Left/Right: change  Space: toggle CPU  a: all  Enter: apply  Esc: close"
+                .to_string(),
+            Color::DarkGray,
+        ),
+    };
+    frame.render_widget(
+        Paragraph::new(Span::styled(text, Style::default().fg(color))).wrap(Wrap { trim: true }),
+        chunks[2],
+    );
+}
+
+/// Largest scroll offset that keeps the last wrapped row of `paragraph` inside `area`.
+fn wrapped_max_scroll(paragraph: &Paragraph<'_>, area: Rect) -> u16 {
+    let rows = paragraph.line_count(area.width);
+    u16::try_from(rows.saturating_sub(usize::from(area.height))).unwrap_or(u16::MAX)
+}
+
+fn detail_lines(detail: &ProcessDetail, units: DisplayUnits) -> Vec<Line<'static>> {
+    let heading = |text: String| {
+        Line::from(Span::styled(
+            text,
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        ))
+    };
+    let path = |value: &Option<std::path::PathBuf>| {
+        value
+            .as_ref()
+            .map(|p| p.display().to_string())
+            .unwrap_or_else(|| "(not readable)".to_string())
+    };
+
+    let mut lines = vec![
+        Line::from(format!(
+            "State {}  PPID {}  Nice {}  Last CPU {}",
+            detail.stat.state,
+            detail.stat.ppid,
+            detail.stat.nice,
+            detail
+                .stat
+                .processor
+                .map(|cpu| cpu.to_string())
+                .unwrap_or_else(|| "?".to_string())
+        )),
+        Line::from(format!(
+            "Started {}",
+            utilities::format_epoch_utc(detail.start_time)
+        )),
+        Line::from(format!(
+            "Command {}",
+            if detail.cmdline.is_empty() {
+                format!("[{}]", detail.stat.comm)
+            } else {
+                detail.cmdline.join(" ")
+            }
+        )),
+        Line::from(format!("Exe     {}", path(&detail.exe))),
+        Line::from(format!("Cwd     {}", path(&detail.cwd))),
+        Line::from(format!(
+            "Cgroup  {}",
+            detail.cgroup.as_deref().unwrap_or("(unknown)")
+        )),
+        Line::default(),
+        heading("Memory".to_string()),
+    ];
+
+    match detail.memory {
+        Some(memory) => lines.push(Line::from(format!(
+            "RSS {}  PSS {}  USS {}  Swap {} (PSS {})",
+            memory.rss.display(units.bytes),
+            memory.pss.display(units.bytes),
+            memory.uss.display(units.bytes),
+            memory.swap.display(units.bytes),
+            memory.swap_pss.display(units.bytes)
+        ))),
+        None => lines.push(Line::from("smaps_rollup not readable")),
+    }
+
+    lines.push(Line::default());
+    lines.push(heading("Status".to_string()));
+    lines.extend(
+        detail
+            .status
+            .iter()
+            .map(|(key, value)| Line::from(format!("{key:<27} {value}"))),
+    );
+
+    lines.push(Line::default());
+    lines.push(heading(format!("Threads ({})", detail.threads.len())));
+    lines.extend(
+        detail
+            .threads
+            .iter()
+            .map(|(tid, name)| Line::from(format!("{tid:>8}  {name}"))),
+    );
+
+    lines.push(Line::default());
+    match &detail.fds {
+        Some(fds) => {
+            lines.push(heading(format!("Open files ({})", fds.len())));
+            lines.extend(
+                fds.iter()
+                    .map(|(fd, target)| Line::from(format!("{fd:>5} -> {target}"))),
+            );
+        }
+        None => lines.push(heading("Open files (not readable)".to_string())),
+    }
+
+    lines.push(Line::default());
+    lines.push(heading("Limits (soft / hard)".to_string()));
+    lines.extend(detail.limits.iter().map(|limit| {
+        Line::from(format!(
+            "{:<26} {} / {} {}",
+            limit.name, limit.soft, limit.hard, limit.units
+        ))
+    }));
+
+    lines.push(Line::default());
+    lines.push(heading("Namespaces".to_string()));
+    lines.extend(
+        detail
+            .namespaces
+            .iter()
+            .map(|(name, target)| Line::from(format!("{name:<18} {target}"))),
+    );
+
+    lines.push(Line::default());
+    match &detail.environ {
+        Some(environ) => {
+            lines.push(heading(format!("Environment ({})", environ.len())));
+            lines.extend(environ.iter().map(|var| Line::from(var.clone())));
+        }
+        None => lines.push(heading("Environment (not readable)".to_string())),
+    }
+
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::{backend::TestBackend, Terminal};
+
+    #[test]
+    fn detail_scroll_reaches_the_end_of_a_wrapped_line() {
+        let text = format!("Command {} END", "x".repeat(40));
+        let paragraph =
+            Paragraph::new(vec![Line::from("Header"), Line::from(text)]).wrap(Wrap { trim: false });
+        let area = Rect::new(0, 0, 10, 3);
+        let max_scroll = wrapped_max_scroll(&paragraph, area);
+        // "Header" plus the 52-character line wrapped at 10 columns, minus 3 visible rows.
+        assert_eq!(max_scroll, 4);
+
+        let mut terminal = Terminal::new(TestBackend::new(10, 3)).unwrap();
+        terminal
+            .draw(|frame| frame.render_widget(paragraph.scroll((max_scroll, 0)), area))
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        let last_row: String = (0..10).map(|x| buffer.get(x, 2).symbol()).collect();
+        assert!(last_row.contains("END"), "{last_row:?}");
+    }
+}
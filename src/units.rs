@@ -0,0 +1,235 @@
+//! Typed quantities that carry their unit from the collectors to the UI.
+
+use std::{
+    iter::Sum,
+    ops::{Add, AddAssign},
+};
+
+/// How byte quantities are scaled and labelled.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ByteUnits {
+    /// Powers of 1024 labelled KiB, MiB, GiB, ...
+    #[default]
+    Iec,
+    /// Powers of 1000 labelled kB, MB, GB, ...
+    Si,
+}
+
+impl ByteUnits {
+    /// The other byte unit system.
+    pub fn toggled(self) -> Self {
+        match self {
+            ByteUnits::Iec => ByteUnits::Si,
+            ByteUnits::Si => ByteUnits::Iec,
+        }
+    }
+}
+
+/// Scale used for temperatures.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TemperatureUnit {
+    #[default]
+    Celsius,
+    Fahrenheit,
+}
+
+impl TemperatureUnit {
+    /// The other temperature scale.
+    pub fn toggled(self) -> Self {
+        match self {
+            TemperatureUnit::Celsius => TemperatureUnit::Fahrenheit,
+            TemperatureUnit::Fahrenheit => TemperatureUnit::Celsius,
+        }
+    }
+}
+
+/// User-selected display units.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DisplayUnits {
+    pub bytes: ByteUnits,
+    pub temperature: TemperatureUnit,
+}
+
+/// A size in bytes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Bytes(pub u64);
+
+impl Bytes {
+    /// Convert a KiB count (as reported by `/proc` files with a `kB` suffix).
+    pub fn from_kib(kib: u64) -> Self {
+        Bytes(kib.saturating_mul(1024))
+    }
+
+    /// Subtract, clamping at zero.
+    pub fn saturating_sub(self, other: Bytes) -> Bytes {
+        Bytes(self.0.saturating_sub(other.0))
+    }
+
+    /// This quantity as a fraction of `total`, or 0 when `total` is zero.
+    pub fn ratio_of(self, total: Bytes) -> f64 {
+        if total.0 == 0 {
+            0.0
+        } else {
+            self.0 as f64 / total.0 as f64
+        }
+    }
+
+    /// Format with one decimal place in the chosen unit system.
+    pub fn display(self, units: ByteUnits) -> String {
+        format_scaled(self.0 as f64, units)
+    }
+}
+
+impl Add for Bytes {
+    type Output = Bytes;
+
+    fn add(self, other: Bytes) -> Bytes {
+        Bytes(self.0.saturating_add(other.0))
+    }
+}
+
+impl AddAssign for Bytes {
+    fn add_assign(&mut self, other: Bytes) {
+        *self = *self + other;
+    }
+}
+
+impl Sum for Bytes {
+    fn sum<I: Iterator<Item = Bytes>>(iter: I) -> Bytes {
+        iter.fold(Bytes(0), Add::add)
+    }
+}
+
+/// A throughput in bytes per second.
+#[derive(Debug, Clone, Copy, Default, PartialEq, PartialOrd)]
+pub struct ByteRate(pub f64);
+
+impl ByteRate {
+    /// Rate of change between two cumulative byte counters over `secs` seconds.
+    pub fn between(before: Bytes, after: Bytes, secs: f64) -> Option<Self> {
+        (secs > 0.0).then(|| ByteRate(after.saturating_sub(before).0 as f64 / secs))
+    }
+
+    /// Format as a size per second in the chosen unit system.
+    pub fn display(self, units: ByteUnits) -> String {
+        format!("{}/s", format_scaled(self.0.max(0.0), units))
+    }
+}
+
+/// A clock frequency in hertz.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Frequency(pub u64);
+
+impl Frequency {
+    /// Convert from megahertz (as reported by sysinfo).
+    pub fn from_mhz(mhz: u64) -> Self {
+        Frequency(mhz.saturating_mul(1_000_000))
+    }
+
+    /// Hertz value.
+    pub fn as_hz(self) -> u64 {
+        self.0
+    }
+
+    /// Format in MHz or GHz (frequencies are always decimal).
+    pub fn display(self) -> String {
+        let hz = self.0 as f64;
+        if hz >= 1e9 {
+            format!("{:.2} GHz", hz / 1e9)
+        } else {
+            format!("{:.0} MHz", hz / 1e6)
+        }
+    }
+}
+
+/// A temperature, stored in degrees Celsius.
+#[derive(Debug, Clone, Copy, Default, PartialEq, PartialOrd)]
+pub struct Temperature(pub f32);
+
+impl Temperature {
+    /// Degrees Celsius.
+    pub fn celsius(self) -> f32 {
+        self.0
+    }
+
+    /// Degrees Fahrenheit.
+    pub fn fahrenheit(self) -> f32 {
+        self.0 * 9.0 / 5.0 + 32.0
+    }
+
+    /// Format as whole degrees in the chosen scale.
+    pub fn display(self, unit: TemperatureUnit) -> String {
+        match unit {
+            TemperatureUnit::Celsius => format!("{:.0}°C", self.celsius()),
+            TemperatureUnit::Fahrenheit => format!("{:.0}°F", self.fahrenheit()),
+        }
+    }
+}
+
+fn format_scaled(value: f64, units: ByteUnits) -> String {
+    let (base, labels): (f64, [&str; 6]) = match units {
+        ByteUnits::Iec => (1024.0, ["B", "KiB", "MiB", "GiB", "TiB", "PiB"]),
+        ByteUnits::Si => (1000.0, ["B", "kB", "MB", "GB", "TB", "PB"]),
+    };
+    let mut unit = 0;
+    let mut value = value;
+    while value >= base && unit < labels.len() - 1 {
+        value /= base;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{:.0} {}", value, labels[unit])
+    } else {
+        format!("{:.1} {}", value, labels[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bytes_display_iec_and_si() {
+        assert_eq!(Bytes(512).display(ByteUnits::Iec), "512 B");
+        assert_eq!(Bytes(1024).display(ByteUnits::Iec), "1.0 KiB");
+        assert_eq!(Bytes(1536).display(ByteUnits::Iec), "1.5 KiB");
+        assert_eq!(Bytes(1024 * 1024).display(ByteUnits::Iec), "1.0 MiB");
+        assert_eq!(Bytes(1000).display(ByteUnits::Si), "1.0 kB");
+        assert_eq!(Bytes(1024).display(ByteUnits::Si), "1.0 kB");
+        assert_eq!(Bytes(2_500_000_000).display(ByteUnits::Si), "2.5 GB");
+    }
+
+    #[test]
+    fn bytes_from_kib_converts() {
+        assert_eq!(Bytes::from_kib(0).display(ByteUnits::Iec), "0 B");
+        assert_eq!(Bytes::from_kib(1).display(ByteUnits::Iec), "1.0 KiB");
+        assert_eq!(Bytes::from_kib(1024).display(ByteUnits::Iec), "1.0 MiB");
+    }
+
+    #[test]
+    fn bytes_arithmetic_and_ratio() {
+        assert_eq!(Bytes(5).saturating_sub(Bytes(7)), Bytes(0));
+        assert_eq!([Bytes(1), Bytes(2)].into_iter().sum::<Bytes>(), Bytes(3));
+        assert_eq!(Bytes(25).ratio_of(Bytes(100)), 0.25);
+        assert_eq!(Bytes(25).ratio_of(Bytes(0)), 0.0);
+    }
+
+    #[test]
+    fn rates_frequencies_and_temperatures() {
+        assert_eq!(
+            ByteRate::between(Bytes(0), Bytes(2048), 2.0).map(|r| r.display(ByteUnits::Iec)),
+            Some("1.0 KiB/s".to_string())
+        );
+        assert_eq!(ByteRate::between(Bytes(0), Bytes(1), 0.0), None);
+        assert_eq!(Frequency::from_mhz(3200).display(), "3.20 GHz");
+        assert_eq!(Frequency::from_mhz(800).display(), "800 MHz");
+        assert_eq!(
+            Temperature(100.0).display(TemperatureUnit::Celsius),
+            "100°C"
+        );
+        assert_eq!(
+            Temperature(100.0).display(TemperatureUnit::Fahrenheit),
+            "212°F"
+        );
+    }
+}
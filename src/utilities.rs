@@ -3,6 +3,10 @@
 /// Shared quit instructions string used across the UI.
 pub const QUIT_INSTRUCTIONS: &str = "Press F10 or q/Q to quit.";
 
+/// Shared key binding hints shown next to the quit instructions.
+pub const KEY_HINTS: &str =
+    "Tab: screen  t: topology  h: heatmap  p: pressure  u/f: units  m/s/g: memory/sort/group  e: sched  x/c/o: threads/cores/occupancy  l: log  Enter/Esc: detail";
+
 #[allow(dead_code)]
 /// Print the ASCII banner to stdout for non-TUI use.
 pub fn print_banner() {
@@ -24,6 +28,62 @@ pub fn banner_text() -> String {
     .to_string()
 }
 
+/// Format a duration in seconds as `Nd HH:MM:SS`, omitting the day count when zero.
+pub fn format_duration(secs: u64) -> String {
+    let days = secs / 86_400;
+    let hours = (secs % 86_400) / 3_600;
+    let minutes = (secs % 3_600) / 60;
+    let seconds = secs % 60;
+    if days > 0 {
+        format!("{days}d {hours:02}:{minutes:02}:{seconds:02}")
+    } else {
+        format!("{hours:02}:{minutes:02}:{seconds:02}")
+    }
+}
+
+/// Format seconds since the Unix epoch as a UTC `YYYY-MM-DD HH:MM` timestamp.
+pub fn format_epoch_utc(epoch_secs: u64) -> String {
+    let days = (epoch_secs / 86_400) as i64;
+    let secs_of_day = epoch_secs % 86_400;
+
+    // Civil-from-days conversion (Howard Hinnant's algorithm).
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02} UTC",
+        secs_of_day / 3_600,
+        (secs_of_day % 3_600) / 60
+    )
+}
+
+/// Format seconds since the Unix epoch as a UTC `HH:MM:SS` time of day.
+pub fn format_clock_utc(epoch_secs: u64) -> String {
+    let secs_of_day = epoch_secs % 86_400;
+    format!(
+        "{:02}:{:02}:{:02}",
+        secs_of_day / 3_600,
+        (secs_of_day % 3_600) / 60,
+        secs_of_day % 60
+    )
+}
+
+/// Format a per-second rate compactly using k/M suffixes.
+pub fn format_rate(value: f64) -> String {
+    match value {
+        v if v >= 1_000_000.0 => format!("{:.1}M", v / 1_000_000.0),
+        v if v >= 1_000.0 => format!("{:.1}k", v / 1_000.0),
+        v => format!("{:.0}", v),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -42,4 +102,29 @@ mod tests {
         // The banner intentionally omits quit text; ensure the shared constant still matches expectation.
         assert_eq!(QUIT_INSTRUCTIONS, "Press F10 or q/Q to quit.");
     }
+
+    #[test]
+    fn format_duration_includes_days_when_needed() {
+        assert_eq!(format_duration(0), "00:00:00");
+        assert_eq!(format_duration(3_661), "01:01:01");
+        assert_eq!(
+            format_duration(3 * 86_400 + 4 * 3_600 + 12 * 60 + 55),
+            "3d 04:12:55"
+        );
+    }
+
+    #[test]
+    fn format_epoch_utc_converts_dates() {
+        assert_eq!(format_epoch_utc(0), "1970-01-01 00:00 UTC");
+        assert_eq!(format_epoch_utc(951_782_400), "2000-02-29 00:00 UTC");
+        assert_eq!(format_epoch_utc(1_792_394_549), "2026-10-19 07:22 UTC");
+        assert_eq!(format_clock_utc(1_792_394_549), "07:22:29");
+    }
+
+    #[test]
+    fn format_rate_uses_suffixes() {
+        assert_eq!(format_rate(12.4), "12");
+        assert_eq!(format_rate(12_345.0), "12.3k");
+        assert_eq!(format_rate(4_200_000.0), "4.2M");
+    }
 }